@@ -0,0 +1,390 @@
+use std::f64::consts::PI;
+
+use num_bigint::BigInt;
+use num_complex::Complex64;
+use num_rational::BigRational;
+use num_traits::{FromPrimitive, Signed};
+
+use super::{decimal::Decimal, parser::expression::Literal};
+
+pub enum Angular {
+    None,
+    // Only the last argument is an angle, like `theta` in `polar(r, theta)`.
+    Argument,
+    Result,
+}
+
+pub type DecimalFunction = fn(&[Decimal], usize) -> Result<Decimal, String>;
+pub type ComplexFunction = fn(&[Complex64]) -> Complex64;
+
+pub struct Builtin {
+    pub name: &'static str,
+    pub parameters: &'static [&'static str],
+    pub variadic: bool,
+    pub angular: Angular,
+    pub function: fn(&[Literal]) -> Result<Literal, String>,
+    pub decimal: Option<DecimalFunction>,
+    pub complex: Option<ComplexFunction>,
+}
+
+impl Builtin {
+    pub fn signature(&self) -> String {
+        let mut parameters = self.parameters.join(", ");
+
+        if self.variadic {
+            parameters.push_str(", ...");
+        }
+
+        format!("{}({parameters})", self.name)
+    }
+
+    pub fn accepts(&self, count: usize) -> bool {
+        if self.variadic {
+            count >= self.parameters.len()
+        } else {
+            count == self.parameters.len()
+        }
+    }
+}
+
+macro_rules! float_function {
+    ($name:literal, $function:expr, $decimal:expr, $complex:expr) => {
+        float_function!($name, $function, $decimal, $complex, Angular::None)
+    };
+
+    ($name:literal, $function:expr, $decimal:expr, $complex:expr, $angular:expr) => {
+        Builtin {
+            name: $name,
+            parameters: &["x"],
+            variadic: false,
+            angular: $angular,
+            function: |arguments| Ok(Literal::Float($function(arguments[0].to_f64()))),
+            decimal: Some(|arguments, precision| $decimal(&arguments[0], precision)),
+            complex: Some(|arguments| $complex(arguments[0])),
+        }
+    };
+}
+
+macro_rules! rounding_function {
+    ($name:literal, $function:expr, $rational:expr) => {
+        Builtin {
+            name: $name,
+            parameters: &["x"],
+            variadic: false,
+            angular: Angular::None,
+            function: |arguments| match &arguments[0] {
+                integer @ (Literal::Integer(_) | Literal::BigInteger(_)) => Ok(integer.clone()),
+                Literal::Rational(value) => Ok(Literal::from_ratio($rational(value))),
+                Literal::Decimal(value) if value.is_integer() => Ok(arguments[0].clone()),
+                Literal::Decimal(value) => Ok(Literal::from_ratio($rational(&value.to_ratio()))),
+                other => Ok(from_f64($function(other.to_f64()))),
+            },
+            decimal: None,
+            complex: None,
+        }
+    };
+}
+
+pub const BUILTINS: &[Builtin] = &[
+    float_function!("sqrt", f64::sqrt, Decimal::sqrt, Complex64::sqrt),
+    float_function!("cbrt", f64::cbrt, Decimal::cbrt, Complex64::cbrt),
+    Builtin {
+        name: "abs",
+        parameters: &["x"],
+        variadic: false,
+        angular: Angular::None,
+        function: |arguments| match &arguments[0] {
+            Literal::Integer(value) => Ok(match value.checked_abs() {
+                Some(value) => Literal::Integer(value),
+                None => Literal::BigInteger(BigInt::from(*value).abs()),
+            }),
+            Literal::BigInteger(value) => Ok(Literal::BigInteger(value.abs())),
+            Literal::Rational(value) => Ok(Literal::Rational(value.abs())),
+            Literal::Decimal(value) => Ok(Literal::Decimal(value.abs())),
+            other => Ok(Literal::Float(other.to_f64().abs())),
+        },
+        decimal: None,
+        complex: Some(|arguments| Complex64::new(arguments[0].norm(), 0.0)),
+    },
+    Builtin {
+        name: "re",
+        parameters: &["z"],
+        variadic: false,
+        angular: Angular::None,
+        function: |arguments| Ok(arguments[0].clone()),
+        decimal: None,
+        complex: Some(|arguments| Complex64::new(arguments[0].re, 0.0)),
+    },
+    Builtin {
+        name: "im",
+        parameters: &["z"],
+        variadic: false,
+        angular: Angular::None,
+        function: |_| Ok(Literal::Integer(0)),
+        decimal: None,
+        complex: Some(|arguments| Complex64::new(arguments[0].im, 0.0)),
+    },
+    Builtin {
+        name: "conj",
+        parameters: &["z"],
+        variadic: false,
+        angular: Angular::None,
+        function: |arguments| Ok(arguments[0].clone()),
+        decimal: None,
+        complex: Some(|arguments| arguments[0].conj()),
+    },
+    Builtin {
+        name: "arg",
+        parameters: &["z"],
+        variadic: false,
+        angular: Angular::Result,
+        function: |arguments| {
+            Ok(Literal::Float(if arguments[0].to_f64() < 0.0 {
+                PI
+            } else {
+                0.0
+            }))
+        },
+        decimal: Some(|arguments, precision| {
+            Ok(if arguments[0].is_negative() {
+                Decimal::constant("pi", precision).unwrap()
+            } else {
+                Decimal::zero()
+            })
+        }),
+        complex: Some(|arguments| Complex64::new(arguments[0].arg(), 0.0)),
+    },
+    Builtin {
+        name: "polar",
+        parameters: &["r", "theta"],
+        variadic: false,
+        angular: Angular::Argument,
+        function: |arguments| {
+            Ok(Literal::from_complex(Complex64::from_polar(
+                arguments[0].to_f64(),
+                arguments[1].to_f64(),
+            )))
+        },
+        decimal: None,
+        complex: None,
+    },
+    Builtin {
+        name: "rect",
+        parameters: &["x", "y"],
+        variadic: false,
+        angular: Angular::None,
+        function: |arguments| {
+            Ok(Literal::from_complex(Complex64::new(
+                arguments[0].to_f64(),
+                arguments[1].to_f64(),
+            )))
+        },
+        decimal: None,
+        complex: None,
+    },
+    float_function!(
+        "sin",
+        f64::sin,
+        Decimal::sin,
+        Complex64::sin,
+        Angular::Argument
+    ),
+    float_function!(
+        "cos",
+        f64::cos,
+        Decimal::cos,
+        Complex64::cos,
+        Angular::Argument
+    ),
+    float_function!(
+        "tan",
+        f64::tan,
+        Decimal::tan,
+        Complex64::tan,
+        Angular::Argument
+    ),
+    float_function!(
+        "asin",
+        f64::asin,
+        Decimal::asin,
+        Complex64::asin,
+        Angular::Result
+    ),
+    float_function!(
+        "acos",
+        f64::acos,
+        Decimal::acos,
+        Complex64::acos,
+        Angular::Result
+    ),
+    float_function!(
+        "atan",
+        f64::atan,
+        Decimal::atan,
+        Complex64::atan,
+        Angular::Result
+    ),
+    float_function!("sinh", f64::sinh, Decimal::sinh, Complex64::sinh),
+    float_function!("cosh", f64::cosh, Decimal::cosh, Complex64::cosh),
+    float_function!("tanh", f64::tanh, Decimal::tanh, Complex64::tanh),
+    float_function!("asinh", f64::asinh, Decimal::asinh, Complex64::asinh),
+    float_function!("acosh", f64::acosh, Decimal::acosh, Complex64::acosh),
+    float_function!("atanh", f64::atanh, Decimal::atanh, Complex64::atanh),
+    float_function!("ln", f64::ln, Decimal::ln, Complex64::ln),
+    float_function!(
+        "log10",
+        f64::log10,
+        |x: &Decimal, precision| { x.log(&Decimal::from_big(BigInt::from(10)), precision) },
+        Complex64::log10
+    ),
+    float_function!(
+        "log2",
+        f64::log2,
+        |x: &Decimal, precision| { x.log(&Decimal::from_big(BigInt::from(2)), precision) },
+        Complex64::log2
+    ),
+    Builtin {
+        name: "log",
+        parameters: &["base", "x"],
+        variadic: false,
+        angular: Angular::None,
+        function: |arguments| {
+            Ok(Literal::Float(
+                arguments[1].to_f64().log(arguments[0].to_f64()),
+            ))
+        },
+        decimal: Some(|arguments, precision| arguments[1].log(&arguments[0], precision)),
+        complex: Some(|arguments| arguments[1].ln() / arguments[0].ln()),
+    },
+    float_function!("exp", f64::exp, Decimal::exp, Complex64::exp),
+    rounding_function!("floor", f64::floor, BigRational::floor),
+    rounding_function!("ceil", f64::ceil, BigRational::ceil),
+    rounding_function!("round", f64::round, BigRational::round),
+    rounding_function!("trunc", f64::trunc, BigRational::trunc),
+    Builtin {
+        name: "float",
+        parameters: &["x"],
+        variadic: false,
+        angular: Angular::None,
+        function: |arguments| Ok(Literal::Float(arguments[0].to_f64())),
+        decimal: None,
+        complex: None,
+    },
+    Builtin {
+        name: "min",
+        parameters: &["x"],
+        variadic: true,
+        angular: Angular::None,
+        function: |arguments| Ok(extremum(arguments, |candidate, best| candidate < best)),
+        decimal: None,
+        complex: None,
+    },
+    Builtin {
+        name: "max",
+        parameters: &["x"],
+        variadic: true,
+        angular: Angular::None,
+        function: |arguments| Ok(extremum(arguments, |candidate, best| candidate > best)),
+        decimal: None,
+        complex: None,
+    },
+    Builtin {
+        name: "hypot",
+        parameters: &["x", "y"],
+        variadic: false,
+        angular: Angular::None,
+        function: |arguments| {
+            Ok(Literal::Float(
+                arguments[0].to_f64().hypot(arguments[1].to_f64()),
+            ))
+        },
+        decimal: Some(|arguments, precision| arguments[0].hypot(&arguments[1], precision)),
+        complex: None,
+    },
+    Builtin {
+        name: "atan2",
+        parameters: &["y", "x"],
+        variadic: false,
+        angular: Angular::Result,
+        function: |arguments| {
+            Ok(Literal::Float(
+                arguments[0].to_f64().atan2(arguments[1].to_f64()),
+            ))
+        },
+        decimal: Some(|arguments, precision| arguments[0].atan2(&arguments[1], precision)),
+        complex: None,
+    },
+];
+
+pub fn lookup(name: &str) -> Option<&'static Builtin> {
+    BUILTINS.iter().find(|builtin| builtin.name == name)
+}
+
+fn from_f64(value: f64) -> Literal {
+    match BigInt::from_f64(value) {
+        Some(value) => Literal::from_big(value),
+        None => Literal::Float(value),
+    }
+}
+
+fn extremum(arguments: &[Literal], better: fn(f64, f64) -> bool) -> Literal {
+    arguments
+        .iter()
+        .skip(1)
+        .fold(arguments[0].clone(), |best, candidate| {
+            if better(candidate.to_f64(), best.to_f64()) {
+                candidate.clone()
+            } else {
+                best
+            }
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::Engine;
+
+    fn evaluate(source: &str) -> Result<String, String> {
+        Engine::new()
+            .eval(source)
+            .map(|value| value.to_string())
+            .map_err(|error| error.to_string())
+    }
+
+    #[test]
+    fn the_math_library_is_registered() {
+        for name in [
+            "sqrt", "cbrt", "abs", "sin", "cos", "tan", "asin", "acos", "atan", "sinh", "cosh",
+            "tanh", "asinh", "acosh", "atanh", "ln", "log10", "log2", "log", "exp", "floor",
+            "ceil", "round", "trunc", "min", "max", "hypot", "atan2",
+        ] {
+            assert!(lookup(name).is_some(), "`{name}` is missing");
+        }
+    }
+
+    #[test]
+    fn calls_take_comma_separated_arguments() {
+        assert_eq!(evaluate("hypot(3, 4)").unwrap(), "5");
+        assert_eq!(evaluate("log(2, 8)").unwrap(), "3");
+        assert_eq!(evaluate("max(1, 5, 3)").unwrap(), "5");
+        assert_eq!(evaluate("min(2)").unwrap(), "2");
+        assert_eq!(evaluate("floor(-1.5) + trunc(-1.5)").unwrap(), "-3");
+    }
+
+    #[test]
+    fn calls_check_the_number_of_arguments() {
+        assert_eq!(
+            evaluate("sqrt(1, 2)").unwrap_err(),
+            "runtime error: `sqrt` takes 1 argument(s) but 2 were given"
+        );
+        assert_eq!(
+            evaluate("max()").unwrap_err(),
+            "runtime error: `max` takes at least 1 argument(s) but 0 were given"
+        );
+        assert_eq!(
+            evaluate("sine(1)").unwrap_err(),
+            "runtime error: undefined function `sine`"
+        );
+    }
+}
@@ -0,0 +1,102 @@
+use std::f64::consts;
+
+use num_complex::Complex64;
+
+pub struct Constant {
+    pub name: &'static str,
+    pub value: Complex64,
+    pub description: &'static str,
+}
+
+pub const CONSTANTS: &[Constant] = &[
+    Constant {
+        name: "pi",
+        value: Complex64::new(consts::PI, 0.0),
+        description: "ratio of a circle's circumference to its diameter",
+    },
+    Constant {
+        name: "e",
+        value: Complex64::new(consts::E, 0.0),
+        description: "Euler's number",
+    },
+    Constant {
+        name: "i",
+        value: Complex64::new(0.0, 1.0),
+        description: "imaginary unit",
+    },
+    Constant {
+        name: "tau",
+        value: Complex64::new(consts::TAU, 0.0),
+        description: "ratio of a circle's circumference to its radius",
+    },
+    Constant {
+        name: "phi",
+        value: Complex64::new(1.618_033_988_749_895, 0.0),
+        description: "golden ratio",
+    },
+    Constant {
+        name: "inf",
+        value: Complex64::new(f64::INFINITY, 0.0),
+        description: "positive infinity",
+    },
+    Constant {
+        name: "nan",
+        value: Complex64::new(f64::NAN, 0.0),
+        description: "not a number",
+    },
+    Constant {
+        name: "c",
+        value: Complex64::new(299_792_458.0, 0.0),
+        description: "speed of light in vacuum [m/s]",
+    },
+    Constant {
+        name: "h",
+        value: Complex64::new(6.626_070_15e-34, 0.0),
+        description: "Planck constant [J s]",
+    },
+    Constant {
+        name: "hbar",
+        value: Complex64::new(1.054_571_817e-34, 0.0),
+        description: "reduced Planck constant [J s]",
+    },
+    Constant {
+        name: "G",
+        value: Complex64::new(6.674_30e-11, 0.0),
+        description: "Newtonian constant of gravitation [m^3 / (kg s^2)]",
+    },
+    Constant {
+        name: "k_B",
+        value: Complex64::new(1.380_649e-23, 0.0),
+        description: "Boltzmann constant [J/K]",
+    },
+    Constant {
+        name: "N_A",
+        value: Complex64::new(6.022_140_76e23, 0.0),
+        description: "Avogadro constant [1/mol]",
+    },
+    Constant {
+        name: "e_charge",
+        value: Complex64::new(1.602_176_634e-19, 0.0),
+        description: "elementary charge [C]",
+    },
+    Constant {
+        name: "m_e",
+        value: Complex64::new(9.109_383_701_5e-31, 0.0),
+        description: "electron mass [kg]",
+    },
+    Constant {
+        name: "m_p",
+        value: Complex64::new(1.672_621_923_69e-27, 0.0),
+        description: "proton mass [kg]",
+    },
+    Constant {
+        name: "eps_0",
+        value: Complex64::new(8.854_187_812_8e-12, 0.0),
+        description: "vacuum electric permittivity [F/m]",
+    },
+    Constant {
+        name: "mu_0",
+        value: Complex64::new(1.256_637_062_12e-6, 0.0),
+        description: "vacuum magnetic permeability [N/A^2]",
+    },
+];
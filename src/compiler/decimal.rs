@@ -0,0 +1,1003 @@
+use std::{cmp::Ordering, iter::successors};
+
+use num_bigint::{BigInt, Sign};
+use num_integer::Integer;
+use num_rational::BigRational;
+use num_traits::{One, Pow, Signed, ToPrimitive, Zero};
+
+pub const DEFAULT_PRECISION: usize = 34;
+
+// Extra digits carried through intermediate steps so that results are correct to the requested
+// precision after the final rounding.
+pub const GUARD_DIGITS: usize = 10;
+
+// Decimal exponents beyond this are rejected rather than risking an overflowing `i64` exponent.
+const MAX_EXPONENT: i64 = 1_000_000_000_000_000;
+
+// `mantissa * 10^exponent`, kept without trailing zeros in the mantissa so that equal values have
+// equal representations.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Decimal {
+    mantissa: BigInt,
+    exponent: i64,
+}
+
+impl Decimal {
+    fn new(mut mantissa: BigInt, mut exponent: i64) -> Self {
+        if mantissa.is_zero() {
+            return Self::zero();
+        }
+
+        // Every trailing zero brings a factor of two, which bounds their count; they are stripped
+        // in chunks of 10^(2^k), largest first, instead of one division per zero.
+        let bound = mantissa.trailing_zeros().unwrap_or(0);
+
+        let powers = successors(Some((1, BigInt::from(10))), |(count, power)| {
+            (count * 2 <= bound).then(|| (count * 2, power * power))
+        })
+        .take_while(|(count, _)| *count <= bound)
+        .collect::<Vec<_>>();
+
+        for (count, power) in powers.iter().rev() {
+            let (quotient, remainder) = mantissa.div_rem(power);
+
+            if remainder.is_zero() {
+                mantissa = quotient;
+                exponent += *count as i64;
+            }
+        }
+
+        Self { mantissa, exponent }
+    }
+
+    pub fn zero() -> Self {
+        Self {
+            mantissa: BigInt::zero(),
+            exponent: 0,
+        }
+    }
+
+    pub fn one() -> Self {
+        Self::from_big(BigInt::one())
+    }
+
+    pub fn from_big(value: BigInt) -> Self {
+        Self::new(value, 0)
+    }
+
+    pub fn from_ratio(value: &BigRational, precision: usize) -> Self {
+        Self::from_big(value.numer().clone())
+            .div(&Self::from_big(value.denom().clone()), precision)
+            .unwrap()
+    }
+
+    pub fn from_f64(value: f64) -> Option<Self> {
+        if !value.is_finite() {
+            return None;
+        }
+
+        // The shortest representation that round-trips, so `0.1` becomes exactly `0.1`.
+        let text = format!("{value:e}");
+        let (mantissa, exponent) = text.split_once('e').unwrap();
+        let decimal = Self::parse(mantissa);
+
+        Some(Self::new(
+            decimal.mantissa,
+            decimal.exponent + exponent.parse::<i64>().unwrap(),
+        ))
+    }
+
+    pub fn parse(text: &str) -> Self {
+        let (sign, text) = match text.strip_prefix('-') {
+            Some(rest) => (-1, rest),
+            None => (1, text),
+        };
+
+        let (whole, fraction) = text.split_once('.').unwrap_or((text, ""));
+        let digits = format!("{whole}{fraction}");
+
+        let mantissa = if digits.is_empty() {
+            BigInt::zero()
+        } else {
+            digits.parse::<BigInt>().unwrap()
+        };
+
+        Self::new(mantissa * sign, -(fraction.len() as i64))
+    }
+
+    pub fn constant(name: &str, precision: usize) -> Option<Self> {
+        let working = precision + GUARD_DIGITS;
+
+        let value = match name {
+            "pi" => pi(working),
+            "tau" => pi(working).mul(&Self::from_big(BigInt::from(2)), working),
+            "e" => Self::one().exp(working).ok()?,
+            "phi" => Self::from_big(BigInt::from(5))
+                .sqrt(working)
+                .ok()?
+                .add(&Self::one(), working)
+                .div(&Self::from_big(BigInt::from(2)), working)
+                .ok()?,
+            _ => return None,
+        };
+
+        Some(value.round(precision))
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        format!("{}e{}", self.mantissa, self.exponent)
+            .parse()
+            .unwrap()
+    }
+
+    pub fn to_ratio(&self) -> BigRational {
+        if self.exponent >= 0 {
+            return BigRational::from_integer(
+                &self.mantissa * BigInt::from(10).pow(self.exponent as u64),
+            );
+        }
+
+        // Anything below one in magnitude rounds the same as a tenth of it, which avoids a
+        // gigantic denominator for tiny values.
+        let exponent = self.exponent.max(-(digits(&self.mantissa) + 1));
+
+        BigRational::new(
+            self.mantissa.clone(),
+            BigInt::from(10).pow(exponent.unsigned_abs()),
+        )
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.mantissa.is_zero()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.mantissa.is_negative()
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.exponent >= 0
+    }
+
+    pub fn sign(&self) -> Sign {
+        self.mantissa.sign()
+    }
+
+    pub fn mantissa(&self) -> &BigInt {
+        &self.mantissa
+    }
+
+    pub fn exponent(&self) -> i64 {
+        self.exponent
+    }
+
+    // The power of ten of the leading digit.
+    pub fn adjusted(&self) -> i64 {
+        digits(&self.mantissa) - 1 + self.exponent
+    }
+
+    pub fn neg(&self) -> Self {
+        Self {
+            mantissa: -&self.mantissa,
+            exponent: self.exponent,
+        }
+    }
+
+    pub fn abs(&self) -> Self {
+        Self {
+            mantissa: self.mantissa.abs(),
+            exponent: self.exponent,
+        }
+    }
+
+    // Rounds half to even, to `precision` significant digits.
+    pub fn round(&self, precision: usize) -> Self {
+        let excess = digits(&self.mantissa) - precision as i64;
+
+        if excess <= 0 {
+            return self.clone();
+        }
+
+        let divisor = BigInt::from(10).pow(excess as u64);
+        let (mut quotient, remainder) = self.mantissa.div_rem(&divisor);
+        let step = self.mantissa.signum();
+
+        match (remainder.abs() * BigInt::from(2)).cmp(&divisor) {
+            Ordering::Greater => quotient += step,
+            Ordering::Equal if quotient.is_odd() => quotient += step,
+            _ => {}
+        }
+
+        Self::new(quotient, self.exponent + excess)
+    }
+
+    pub fn add(&self, other: &Self, precision: usize) -> Self {
+        if self.is_zero() {
+            return other.round(precision);
+        }
+
+        if other.is_zero() {
+            return self.round(precision);
+        }
+
+        // An operand too small to affect any retained digit is dropped rather than aligned,
+        // which could take an enormous number of digits.
+        let gap = self.adjusted() - other.adjusted();
+
+        if gap > precision as i64 + 2 {
+            return self.round(precision);
+        }
+
+        if -gap > precision as i64 + 2 {
+            return other.round(precision);
+        }
+
+        let (left, right, exponent) = self.align(other);
+
+        Self::new(left + right, exponent).round(precision)
+    }
+
+    pub fn sub(&self, other: &Self, precision: usize) -> Self {
+        self.add(&other.neg(), precision)
+    }
+
+    pub fn mul(&self, other: &Self, precision: usize) -> Self {
+        Self::new(
+            &self.mantissa * &other.mantissa,
+            self.exponent + other.exponent,
+        )
+        .round(precision)
+    }
+
+    pub fn div(&self, other: &Self, precision: usize) -> Result<Self, String> {
+        if other.is_zero() {
+            return Err("division by zero".to_string());
+        }
+
+        let shift =
+            (precision as i64 + 2 + digits(&other.mantissa) - digits(&self.mantissa)).max(0);
+        let scaled = &self.mantissa * BigInt::from(10).pow(shift as u64);
+        let (quotient, remainder) = scaled.div_rem(&other.mantissa);
+
+        // A nonzero remainder is kept as a final sticky digit so that rounding sees it.
+        let (quotient, shift) = if remainder.is_zero() {
+            (quotient, shift)
+        } else {
+            (
+                quotient * 10 + scaled.signum() * other.mantissa.signum(),
+                shift + 1,
+            )
+        };
+
+        Ok(Self::new(quotient, self.exponent - other.exponent - shift).round(precision))
+    }
+
+    // The remainder of floored division, with the sign of the divisor.
+    pub fn rem(&self, other: &Self, precision: usize) -> Result<Self, String> {
+        if other.is_zero() {
+            return Err("division by zero".to_string());
+        }
+
+        if self.abs() < other.abs() {
+            return Ok(
+                if self.is_zero() || self.is_negative() == other.is_negative() {
+                    self.round(precision)
+                } else {
+                    self.add(other, precision)
+                },
+            );
+        }
+
+        // Aligning a dividend many orders of magnitude above the divisor would take as many
+        // digits, but only its power of ten modulo the divisor matters.
+        if self.exponent > other.exponent {
+            let scale = BigInt::from(10).modpow(
+                &BigInt::from(self.exponent - other.exponent),
+                &other.mantissa.abs(),
+            );
+
+            return Ok(Self::new(
+                (&self.mantissa * scale).mod_floor(&other.mantissa),
+                other.exponent,
+            )
+            .round(precision));
+        }
+
+        // The dividend is at least as large as the divisor here, so it has at least as many
+        // digits as the exponents differ.
+        let (left, right, exponent) = self.align(other);
+
+        Ok(Self::new(left.mod_floor(&right), exponent).round(precision))
+    }
+
+    pub fn pow(&self, exponent: &Self, precision: usize) -> Result<Self, String> {
+        if exponent.is_integer() {
+            if exponent.adjusted() > 1000 {
+                return Err(out_of_range());
+            }
+
+            return self.powi(exponent.to_ratio().to_integer(), precision);
+        }
+
+        if self.is_zero() {
+            return if exponent.is_negative() {
+                Err("division by zero".to_string())
+            } else {
+                Ok(Self::zero())
+            };
+        }
+
+        if self.is_negative() {
+            return Err("cannot raise a negative number to a fractional power".to_string());
+        }
+
+        let working = precision + GUARD_DIGITS + exponent.adjusted().max(0) as usize;
+
+        self.ln(working)?
+            .mul(exponent, working)
+            .exp(working)
+            .map(|value| value.round(precision))
+    }
+
+    pub fn powi(&self, exponent: BigInt, precision: usize) -> Result<Self, String> {
+        if exponent.is_zero() {
+            return Ok(Self::one());
+        }
+
+        if self.is_zero() {
+            return if exponent.is_negative() {
+                Err("division by zero".to_string())
+            } else {
+                Ok(Self::zero())
+            };
+        }
+
+        if self.abs() == Self::one() {
+            return Ok(if exponent.is_even() {
+                Self::one()
+            } else {
+                self.clone()
+            });
+        }
+
+        let count = exponent
+            .abs()
+            .to_i64()
+            .filter(|count| (self.adjusted().abs() + 1).saturating_mul(*count) < MAX_EXPONENT)
+            .ok_or_else(out_of_range)?;
+
+        let working = precision + GUARD_DIGITS + digits(&BigInt::from(count)) as usize;
+        let mut base = self.clone();
+        let mut result = Self::one();
+        let mut remaining = count;
+
+        while remaining > 0 {
+            if remaining & 1 == 1 {
+                result = result.mul(&base, working);
+            }
+
+            remaining >>= 1;
+
+            if remaining > 0 {
+                base = base.mul(&base, working);
+            }
+        }
+
+        if exponent.is_negative() {
+            result = Self::one().div(&result, working)?;
+        }
+
+        Ok(result.round(precision))
+    }
+
+    pub fn sqrt(&self, precision: usize) -> Result<Self, String> {
+        if self.is_negative() {
+            return Err("cannot take the square root of a negative number".to_string());
+        }
+
+        self.root(2, precision)
+    }
+
+    pub fn cbrt(&self, precision: usize) -> Result<Self, String> {
+        self.root(3, precision)
+    }
+
+    fn root(&self, degree: u32, precision: usize) -> Result<Self, String> {
+        if self.is_zero() {
+            return Ok(Self::zero());
+        }
+
+        let degree = i64::from(degree);
+        let wanted = (precision as i64 + 2) * degree;
+        let mut shift = (wanted - digits(&self.mantissa)).max(0);
+        shift += (self.exponent - shift).rem_euclid(degree);
+
+        let scaled = &self.mantissa * BigInt::from(10).pow(shift as u64);
+        let root = scaled.nth_root(degree as u32);
+
+        let exponent = (self.exponent - shift) / degree;
+
+        let (root, exponent) = if Pow::pow(&root, degree as u32) == scaled {
+            (root, exponent)
+        } else {
+            (root * 10 + scaled.signum(), exponent - 1)
+        };
+
+        Ok(Self::new(root, exponent).round(precision))
+    }
+
+    pub fn exp(&self, precision: usize) -> Result<Self, String> {
+        if self.is_zero() {
+            return Ok(Self::one());
+        }
+
+        if self.adjusted() >= 15 {
+            return Err(out_of_range());
+        }
+
+        // exp(x) = exp(x / 2^k)^(2^k), where the reduced argument makes the series converge fast
+        // and each squaring costs a little under a third of a digit.
+        let halvings = (self.abs().to_f64().log2().ceil() + 8.0).max(0.0) as u32;
+        let working = precision + GUARD_DIGITS + halvings as usize * 3 / 10 + 1;
+
+        let reduced = self.div(&Self::from_big(BigInt::one() << halvings), working)?;
+
+        let mut sum = Self::one();
+        let mut term = Self::one();
+
+        for n in 1.. {
+            term = term
+                .mul(&reduced, working)
+                .div(&Self::from_big(BigInt::from(n)), working)?;
+
+            if term.is_zero() || term.adjusted() < sum.adjusted() - working as i64 - 1 {
+                break;
+            }
+
+            sum = sum.add(&term, working);
+        }
+
+        for _ in 0..halvings {
+            sum = sum.mul(&sum, working);
+        }
+
+        Ok(sum.round(precision))
+    }
+
+    pub fn ln(&self, precision: usize) -> Result<Self, String> {
+        if self.is_zero() || self.is_negative() {
+            return Err("cannot take the logarithm of a non-positive number".to_string());
+        }
+
+        if *self == Self::one() {
+            return Ok(Self::zero());
+        }
+
+        let working = precision + GUARD_DIGITS;
+
+        // Halley's iteration triples the correct digits each step, starting from the f64
+        // logarithm of the leading digits.
+        let adjusted = self.adjusted();
+        let leading = Self {
+            mantissa: self.mantissa.clone(),
+            exponent: self.exponent - adjusted,
+        };
+
+        let mut result =
+            Self::from_f64(leading.to_f64().ln() + adjusted as f64 * std::f64::consts::LN_10)
+                .unwrap();
+
+        for _ in 0..10 {
+            let exp = result.exp(working)?;
+            let step = self
+                .sub(&exp, working)
+                .div(&self.add(&exp, working), working)?
+                .mul(&Self::from_big(BigInt::from(2)), working);
+
+            result = result.add(&step, working);
+
+            if step.is_zero() || step.adjusted() < result.adjusted() - working as i64 {
+                break;
+            }
+        }
+
+        Ok(result.round(precision))
+    }
+
+    pub fn log(&self, base: &Self, precision: usize) -> Result<Self, String> {
+        let working = precision + GUARD_DIGITS;
+
+        self.ln(working)?
+            .div(&base.ln(working)?, working)
+            .map(|value| value.round(precision))
+    }
+
+    pub fn sin(&self, precision: usize) -> Result<Self, String> {
+        self.sine_series(true, precision)
+    }
+
+    pub fn cos(&self, precision: usize) -> Result<Self, String> {
+        self.sine_series(false, precision)
+    }
+
+    pub fn tan(&self, precision: usize) -> Result<Self, String> {
+        let working = precision + GUARD_DIGITS;
+
+        self.sin(working)?
+            .div(&self.cos(working)?, working)
+            .map(|value| value.round(precision))
+    }
+
+    fn sine_series(&self, is_sine: bool, precision: usize) -> Result<Self, String> {
+        if self.adjusted() > 1000 {
+            return Err(out_of_range());
+        }
+
+        // The argument is reduced to [-pi, pi], which needs pi to as many more digits as the
+        // argument has before the decimal point.
+        let working = precision + GUARD_DIGITS + self.adjusted().max(0) as usize;
+        let two_pi = pi(working).mul(&Self::from_big(BigInt::from(2)), working);
+        let turns = self.div(&two_pi, working)?.to_ratio().round().to_integer();
+        let reduced = self.sub(&two_pi.mul(&Self::from_big(turns), working), working);
+
+        let square = reduced.mul(&reduced, working).neg();
+        let mut term = if is_sine { reduced } else { Self::one() };
+        let mut sum = term.clone();
+
+        for n in 1.. {
+            let factor = if is_sine {
+                2 * n * (2 * n + 1)
+            } else {
+                (2 * n - 1) * (2 * n)
+            };
+
+            term = term
+                .mul(&square, working)
+                .div(&Self::from_big(BigInt::from(factor)), working)?;
+
+            if term.is_zero() || term.adjusted() < -(working as i64) - 1 {
+                break;
+            }
+
+            sum = sum.add(&term, working);
+        }
+
+        Ok(sum.round(precision))
+    }
+
+    pub fn atan(&self, precision: usize) -> Result<Self, String> {
+        if self.is_zero() {
+            return Ok(Self::zero());
+        }
+
+        let working = precision + GUARD_DIGITS;
+        let one = Self::one();
+
+        // atan(x) = pi/2 - atan(1/x) for x > 1.
+        if self.abs() > one {
+            let half_pi = pi(working).div(&Self::from_big(BigInt::from(2)), working)?;
+            let complement = one.div(&self.abs(), working)?.atan(working)?;
+            let result = half_pi.sub(&complement, working);
+
+            return Ok(result.with_sign_of(self).round(precision));
+        }
+
+        // atan(x) = 2 atan(x / (1 + sqrt(1 + x^2))) shrinks the argument for the series.
+        let mut reduced = self.clone();
+        let mut doublings = 0;
+
+        while reduced.adjusted() > -2 {
+            let root = one
+                .add(&reduced.mul(&reduced, working), working)
+                .sqrt(working)?;
+            reduced = reduced.div(&one.add(&root, working), working)?;
+            doublings += 1;
+        }
+
+        let square = reduced.mul(&reduced, working).neg();
+        let mut power = reduced.clone();
+        let mut sum = reduced.clone();
+
+        for n in 1.. {
+            power = power.mul(&square, working);
+            let term = power.div(&Self::from_big(BigInt::from(2 * n + 1)), working)?;
+
+            if term.is_zero() || term.adjusted() < sum.adjusted() - working as i64 - 1 {
+                break;
+            }
+
+            sum = sum.add(&term, working);
+        }
+
+        Ok(sum
+            .mul(&Self::from_big(BigInt::one() << doublings), working)
+            .round(precision))
+    }
+
+    pub fn asin(&self, precision: usize) -> Result<Self, String> {
+        let working = precision + GUARD_DIGITS;
+        let one = Self::one();
+
+        match self.abs().cmp(&one) {
+            Ordering::Greater => Err("argument must be between -1 and 1".to_string()),
+
+            Ordering::Equal => {
+                let half_pi = pi(working).div(&Self::from_big(BigInt::from(2)), working)?;
+
+                Ok(half_pi.with_sign_of(self).round(precision))
+            }
+
+            Ordering::Less => {
+                let root = one.sub(&self.mul(self, working), working).sqrt(working)?;
+
+                self.div(&root, working)?
+                    .atan(working)
+                    .map(|value| value.round(precision))
+            }
+        }
+    }
+
+    pub fn acos(&self, precision: usize) -> Result<Self, String> {
+        let working = precision + GUARD_DIGITS;
+        let half_pi = pi(working).div(&Self::from_big(BigInt::from(2)), working)?;
+
+        Ok(half_pi.sub(&self.asin(working)?, working).round(precision))
+    }
+
+    pub fn atan2(&self, x: &Self, precision: usize) -> Result<Self, String> {
+        let working = precision + GUARD_DIGITS;
+
+        let result = match (x.sign(), self.sign()) {
+            (Sign::Plus, _) => self.div(x, working)?.atan(working)?,
+            (Sign::Minus, Sign::Minus) => self
+                .div(x, working)?
+                .atan(working)?
+                .sub(&pi(working), working),
+            (Sign::Minus, _) => self
+                .div(x, working)?
+                .atan(working)?
+                .add(&pi(working), working),
+            (Sign::NoSign, Sign::NoSign) => Self::zero(),
+            (Sign::NoSign, _) => pi(working)
+                .div(&Self::from_big(BigInt::from(2)), working)?
+                .with_sign_of(self),
+        };
+
+        Ok(result.round(precision))
+    }
+
+    pub fn sinh(&self, precision: usize) -> Result<Self, String> {
+        let working = precision + GUARD_DIGITS;
+
+        // The series avoids the cancellation in (e^x - e^-x) / 2 for small x.
+        if self.adjusted() < 0 {
+            let square = self.mul(self, working);
+            let mut term = self.clone();
+            let mut sum = self.clone();
+
+            for n in 1.. {
+                term = term
+                    .mul(&square, working)
+                    .div(&Self::from_big(BigInt::from(2 * n * (2 * n + 1))), working)?;
+
+                if term.is_zero() || term.adjusted() < sum.adjusted() - working as i64 - 1 {
+                    break;
+                }
+
+                sum = sum.add(&term, working);
+            }
+
+            return Ok(sum.round(precision));
+        }
+
+        let exp = self.exp(working)?;
+        let inverse = Self::one().div(&exp, working)?;
+
+        exp.sub(&inverse, working)
+            .div(&Self::from_big(BigInt::from(2)), working)
+            .map(|value| value.round(precision))
+    }
+
+    pub fn cosh(&self, precision: usize) -> Result<Self, String> {
+        let working = precision + GUARD_DIGITS;
+        let exp = self.exp(working)?;
+        let inverse = Self::one().div(&exp, working)?;
+
+        exp.add(&inverse, working)
+            .div(&Self::from_big(BigInt::from(2)), working)
+            .map(|value| value.round(precision))
+    }
+
+    pub fn tanh(&self, precision: usize) -> Result<Self, String> {
+        let working = precision + GUARD_DIGITS;
+
+        // Beyond this, e^-2x no longer affects any retained digit.
+        if self.abs() > Self::from_big(BigInt::from(2 * working)) {
+            return Ok(Self::one().with_sign_of(self));
+        }
+
+        self.sinh(working)?
+            .div(&self.cosh(working)?, working)
+            .map(|value| value.round(precision))
+    }
+
+    pub fn asinh(&self, precision: usize) -> Result<Self, String> {
+        let working = precision + GUARD_DIGITS + (-self.adjusted()).max(0) as usize;
+        let magnitude = self.abs();
+        let root = magnitude
+            .mul(&magnitude, working)
+            .add(&Self::one(), working)
+            .sqrt(working)?;
+        let result = magnitude.add(&root, working).ln(working)?;
+
+        Ok(result.with_sign_of(self).round(precision))
+    }
+
+    pub fn acosh(&self, precision: usize) -> Result<Self, String> {
+        if *self < Self::one() {
+            return Err("argument must be at least 1".to_string());
+        }
+
+        let working = precision + GUARD_DIGITS;
+        let root = self
+            .mul(self, working)
+            .sub(&Self::one(), working)
+            .sqrt(working)?;
+
+        self.add(&root, working)
+            .ln(working)
+            .map(|value| value.round(precision))
+    }
+
+    pub fn atanh(&self, precision: usize) -> Result<Self, String> {
+        if self.abs() >= Self::one() {
+            return Err("argument must be strictly between -1 and 1".to_string());
+        }
+
+        let working = precision + GUARD_DIGITS + (-self.adjusted()).max(0) as usize;
+        let one = Self::one();
+
+        one.add(self, working)
+            .div(&one.sub(self, working), working)?
+            .ln(working)?
+            .div(&Self::from_big(BigInt::from(2)), working)
+            .map(|value| value.round(precision))
+    }
+
+    pub fn hypot(&self, other: &Self, precision: usize) -> Result<Self, String> {
+        let working = precision + GUARD_DIGITS;
+
+        self.mul(self, working)
+            .add(&other.mul(other, working), working)
+            .sqrt(precision)
+    }
+
+    fn with_sign_of(self, other: &Self) -> Self {
+        if other.is_negative() {
+            self.neg()
+        } else {
+            self
+        }
+    }
+
+    fn align(&self, other: &Self) -> (BigInt, BigInt, i64) {
+        let exponent = self.exponent.min(other.exponent);
+        let scale = |value: &Self| {
+            &value.mantissa * BigInt::from(10).pow((value.exponent - exponent) as u64)
+        };
+
+        (scale(self), scale(other), exponent)
+    }
+}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let ordering = self.sign().cmp(&other.sign());
+
+        if ordering != Ordering::Equal || self.is_zero() {
+            return ordering;
+        }
+
+        let ordering = match self.adjusted().cmp(&other.adjusted()) {
+            Ordering::Equal => {
+                let (left, right, _) = self.align(other);
+                return left.cmp(&right);
+            }
+            ordering => ordering,
+        };
+
+        if self.is_negative() {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    }
+}
+
+// Machin's formula, pi = 16 atan(1/5) - 4 atan(1/239), in fixed point.
+fn pi(precision: usize) -> Decimal {
+    let scale = BigInt::from(10).pow((precision + GUARD_DIGITS) as u32);
+
+    let arctangent = |inverse: u32| {
+        let inverse = BigInt::from(inverse);
+        let square = &inverse * &inverse;
+        let mut power = &scale / &inverse;
+        let mut sum = power.clone();
+
+        for n in 1u32.. {
+            power /= &square;
+
+            if power.is_zero() {
+                break;
+            }
+
+            let term = &power / BigInt::from(2 * n + 1);
+
+            if n % 2 == 1 {
+                sum -= term;
+            } else {
+                sum += term;
+            }
+        }
+
+        sum
+    };
+
+    let value = arctangent(5) * 16 - arctangent(239) * 4;
+
+    Decimal::new(value, -((precision + GUARD_DIGITS) as i64)).round(precision)
+}
+
+fn digits(value: &BigInt) -> i64 {
+    if value.is_zero() {
+        return 1;
+    }
+
+    value.magnitude().to_string().len() as i64
+}
+
+fn out_of_range() -> String {
+    "result is out of range".to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_digits(actual: Result<Decimal, String>, expected: &str) {
+        let actual = actual.unwrap();
+        let expected = Decimal::parse(expected);
+
+        assert!(
+            actual == expected,
+            "expected {}e{}, got {}e{}",
+            expected.mantissa,
+            expected.exponent,
+            actual.mantissa,
+            actual.exponent
+        );
+    }
+
+    #[test]
+    fn pi() {
+        assert_digits(Ok(Decimal::constant("pi", 10).unwrap()), "3.141592654");
+        assert_digits(
+            Ok(Decimal::constant("pi", 34).unwrap()),
+            "3.141592653589793238462643383279503",
+        );
+        assert_digits(
+            Ok(Decimal::constant("pi", 100).unwrap()),
+            "3.141592653589793238462643383279502884197169399375105820974944592307816406286208998628034825342117068",
+        );
+    }
+
+    #[test]
+    fn e() {
+        assert_digits(Ok(Decimal::constant("e", 10).unwrap()), "2.718281828");
+        assert_digits(
+            Ok(Decimal::constant("e", 34).unwrap()),
+            "2.718281828459045235360287471352662",
+        );
+        assert_digits(
+            Ok(Decimal::constant("e", 100).unwrap()),
+            "2.718281828459045235360287471352662497757247093699959574966967627724076630353547594571382178525166427",
+        );
+    }
+
+    #[test]
+    fn ln() {
+        let two = Decimal::parse("2");
+        let thousandth = Decimal::parse("0.001");
+
+        assert_digits(two.ln(10), "0.6931471806");
+        assert_digits(two.ln(34), "0.6931471805599453094172321214581766");
+        assert_digits(
+            two.ln(100),
+            "0.6931471805599453094172321214581765680755001343602552541206800094933936219696947156058633269964186875",
+        );
+        assert_digits(thousandth.ln(10), "-6.907755279");
+        assert_digits(thousandth.ln(34), "-6.907755278982137052053974364053093");
+        assert_digits(
+            thousandth.ln(100),
+            "-6.907755278982137052053974364053092622803304465886318928099983702902717829032057440707991615268794895",
+        );
+        assert!(Decimal::zero().ln(34).is_err());
+    }
+
+    #[test]
+    fn sin() {
+        let one = Decimal::one();
+        let hundred = Decimal::parse("100");
+
+        assert_digits(one.sin(10), "0.8414709848");
+        assert_digits(one.sin(34), "0.8414709848078965066525023216302990");
+        assert_digits(
+            one.sin(100),
+            "0.8414709848078965066525023216302989996225630607983710656727517099919104043912396689486397435430526959",
+        );
+        assert_digits(hundred.sin(10), "-0.5063656411");
+        assert_digits(hundred.sin(34), "-0.5063656411097587936565576104597854");
+        assert_digits(
+            hundred.sin(100),
+            "-0.5063656411097587936565576104597854320650327212906573234433924735943579134194766964992366645129273922",
+        );
+    }
+
+    #[test]
+    fn atan() {
+        let half = Decimal::parse("0.5");
+        let ten = Decimal::parse("10");
+
+        assert_digits(half.atan(10), "0.4636476090");
+        assert_digits(half.atan(34), "0.4636476090008061162142562314612144");
+        assert_digits(
+            half.atan(100),
+            "0.4636476090008061162142562314612144020285370542861202638109330887201978641657417053006002839848878926",
+        );
+        assert_digits(ten.atan(10), "1.471127674");
+        assert_digits(ten.atan(34), "1.471127674303734591852875571761731");
+        assert_digits(
+            ten.atan(100),
+            "1.471127674303734591852875571761730851855306377183238262471963519343880455695553844893404788236772162",
+        );
+    }
+
+    #[test]
+    fn sqrt() {
+        let two = Decimal::parse("2");
+        let fiftieth = Decimal::parse("0.02");
+
+        assert_digits(two.sqrt(10), "1.414213562");
+        assert_digits(two.sqrt(34), "1.414213562373095048801688724209698");
+        assert_digits(
+            two.sqrt(100),
+            "1.414213562373095048801688724209698078569671875376948073176679737990732478462107038850387534327641573",
+        );
+        assert_digits(fiftieth.sqrt(10), "0.1414213562");
+        assert_digits(fiftieth.sqrt(34), "0.1414213562373095048801688724209698");
+        assert_digits(Decimal::parse("1.44").sqrt(34), "1.2");
+        assert!(Decimal::parse("-1").sqrt(34).is_err());
+    }
+
+    #[test]
+    fn round_half_even() {
+        let round = |value: &str, precision| Ok(Decimal::parse(value).round(precision));
+
+        assert_digits(round("2.5", 1), "2");
+        assert_digits(round("3.5", 1), "4");
+        assert_digits(round("-2.5", 1), "-2");
+        assert_digits(round("-3.5", 1), "-4");
+        assert_digits(round("2.51", 1), "3");
+        assert_digits(round("0.125", 2), "0.12");
+        assert_digits(round("0.135", 2), "0.14");
+        assert_digits(round("12345", 3), "12300");
+        assert_digits(round("999.5", 3), "1000");
+        assert_digits(round("1.5", 34), "1.5");
+    }
+}
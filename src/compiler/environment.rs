@@ -0,0 +1,365 @@
+use std::{collections::BTreeMap, sync::Arc, time::Instant};
+
+use num_bigint::BigInt;
+
+use super::{
+    constants::CONSTANTS,
+    decimal::{self, Decimal},
+    parser::expression::{Expression, Literal},
+};
+
+pub const DEFAULT_MAX_DEPTH: usize = 256;
+pub const MAX_DEPTH_LIMIT: usize = 10_000;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum AngleUnit {
+    Radians,
+    Degrees,
+}
+
+impl AngleUnit {
+    pub fn to_radians(self, angle: f64) -> f64 {
+        match self {
+            Self::Radians => angle,
+            Self::Degrees => angle.to_radians(),
+        }
+    }
+
+    pub fn to_unit(self, radians: f64) -> f64 {
+        match self {
+            Self::Radians => radians,
+            Self::Degrees => radians.to_degrees(),
+        }
+    }
+
+    pub fn decimal_to_radians(self, angle: &Decimal, precision: usize) -> Decimal {
+        match self {
+            Self::Radians => angle.clone(),
+            Self::Degrees => angle
+                .mul(&Decimal::constant("pi", precision).unwrap(), precision)
+                .div(&Decimal::from_big(BigInt::from(180)), precision)
+                .unwrap(),
+        }
+    }
+
+    pub fn decimal_to_unit(self, radians: &Decimal, precision: usize) -> Decimal {
+        match self {
+            Self::Radians => radians.clone(),
+            Self::Degrees => radians
+                .mul(&Decimal::from_big(BigInt::from(180)), precision)
+                .div(&Decimal::constant("pi", precision).unwrap(), precision)
+                .unwrap(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Function {
+    pub parameters: Vec<String>,
+    pub body: Expression,
+}
+
+impl Function {
+    pub fn signature(&self, name: &str) -> String {
+        format!("{name}({})", self.parameters.join(", "))
+    }
+}
+
+pub type Native = Arc<dyn Fn(&[Literal]) -> Result<Literal, String> + Send + Sync>;
+
+#[derive(Clone)]
+pub struct NativeFunction {
+    pub parameters: Vec<String>,
+    pub function: Native,
+}
+
+impl NativeFunction {
+    pub fn signature(&self, name: &str) -> String {
+        format!("{name}({})", self.parameters.join(", "))
+    }
+}
+
+pub struct Environment {
+    variables: BTreeMap<String, Literal>,
+    constants: BTreeMap<String, Literal>,
+    functions: BTreeMap<String, Function>,
+    native_functions: BTreeMap<String, NativeFunction>,
+    results: Vec<Literal>,
+    max_depth: usize,
+    angle_unit: AngleUnit,
+    is_exact: bool,
+    is_decimal: bool,
+    precision: usize,
+}
+
+impl Environment {
+    pub fn new() -> Self {
+        Self {
+            variables: BTreeMap::new(),
+            constants: constants(None),
+            functions: BTreeMap::new(),
+            native_functions: BTreeMap::new(),
+            results: Vec::new(),
+            max_depth: DEFAULT_MAX_DEPTH,
+            angle_unit: AngleUnit::Radians,
+            is_exact: false,
+            is_decimal: false,
+            precision: decimal::DEFAULT_PRECISION,
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Literal> {
+        self.constants
+            .get(name)
+            .or_else(|| self.variables.get(name))
+            .or_else(|| self.result(name))
+    }
+
+    pub fn result(&self, name: &str) -> Option<&Literal> {
+        match name {
+            "ans" | "_" => self.results.last(),
+            _ => name
+                .strip_prefix('$')?
+                .parse::<usize>()
+                .ok()?
+                .checked_sub(1)
+                .and_then(|index| self.results.get(index)),
+        }
+    }
+
+    pub fn record(&mut self, value: Literal) -> usize {
+        self.results.push(value);
+        self.results.len()
+    }
+
+    pub fn results(&self) -> &[Literal] {
+        &self.results
+    }
+
+    pub fn is_constant(&self, name: &str) -> bool {
+        self.constants.contains_key(name)
+    }
+
+    pub fn is_variable(&self, name: &str) -> bool {
+        self.variables.contains_key(name)
+    }
+
+    pub fn define(&mut self, name: String, value: Literal) {
+        self.variables.insert(name, value);
+    }
+
+    pub fn function(&self, name: &str) -> Option<&Function> {
+        self.functions.get(name)
+    }
+
+    pub fn define_function(&mut self, name: String, function: Function) {
+        self.functions.insert(name, function);
+    }
+
+    pub fn native_function(&self, name: &str) -> Option<&NativeFunction> {
+        self.native_functions.get(name)
+    }
+
+    pub fn define_native_function(&mut self, name: String, function: NativeFunction) {
+        self.native_functions.insert(name, function);
+    }
+
+    pub fn constant(&self, name: &str) -> Option<&Literal> {
+        self.constants.get(name)
+    }
+
+    pub fn variables(&self) -> impl Iterator<Item = (&String, &Literal)> {
+        self.variables.iter()
+    }
+
+    pub fn functions(&self) -> impl Iterator<Item = (&String, &Function)> {
+        self.functions.iter()
+    }
+
+    pub fn native_functions(&self) -> impl Iterator<Item = (&String, &NativeFunction)> {
+        self.native_functions.iter()
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.constants
+            .keys()
+            .chain(self.variables.keys())
+            .map(String::as_str)
+    }
+
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = max_depth.min(MAX_DEPTH_LIMIT);
+    }
+
+    pub fn angle_unit(&self) -> AngleUnit {
+        self.angle_unit
+    }
+
+    pub fn set_angle_unit(&mut self, angle_unit: AngleUnit) {
+        self.angle_unit = angle_unit;
+    }
+
+    pub fn is_exact(&self) -> bool {
+        self.is_exact
+    }
+
+    pub fn set_exact(&mut self, is_exact: bool) {
+        self.is_exact = is_exact;
+    }
+
+    pub fn is_decimal(&self) -> bool {
+        self.is_decimal
+    }
+
+    pub fn precision(&self) -> usize {
+        self.precision
+    }
+
+    pub fn set_decimal(&mut self, is_decimal: bool, precision: usize) {
+        if (is_decimal, precision) == (self.is_decimal, self.precision) {
+            return;
+        }
+
+        self.is_decimal = is_decimal;
+        self.precision = precision;
+        self.constants = constants(is_decimal.then_some(precision));
+    }
+}
+
+fn constants(precision: Option<usize>) -> BTreeMap<String, Literal> {
+    CONSTANTS
+        .iter()
+        .map(|constant| {
+            let value = precision
+                .filter(|_| constant.value.im == 0.0)
+                .and_then(|precision| {
+                    Decimal::constant(constant.name, precision)
+                        .or_else(|| Decimal::from_f64(constant.value.re))
+                })
+                .map_or(Literal::from_complex(constant.value), Literal::Decimal);
+
+            (constant.name.to_string(), value)
+        })
+        .collect()
+}
+
+impl Default for Environment {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct Scope<'a> {
+    environment: &'a Environment,
+    locals: BTreeMap<String, Literal>,
+    depth: usize,
+    deadline: Option<Instant>,
+    max_bits: Option<u64>,
+}
+
+impl<'a> Scope<'a> {
+    pub fn new(environment: &'a Environment) -> Self {
+        Self {
+            environment,
+            locals: BTreeMap::new(),
+            depth: 0,
+            deadline: None,
+            max_bits: None,
+        }
+    }
+
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    pub fn with_max_bits(mut self, max_bits: u64) -> Self {
+        self.max_bits = Some(max_bits);
+        self
+    }
+
+    pub fn environment(&self) -> &'a Environment {
+        self.environment
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    pub fn exceeds(&self, value: &Literal) -> bool {
+        self.max_bits
+            .is_some_and(|max_bits| value.bits() > max_bits)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Literal> {
+        self.locals.get(name).or_else(|| self.environment.get(name))
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.locals
+            .keys()
+            .map(String::as_str)
+            .chain(self.environment.names())
+    }
+
+    pub fn enter(&self, locals: BTreeMap<String, Literal>) -> Option<Self> {
+        (self.depth < self.environment.max_depth).then(|| Self {
+            environment: self.environment,
+            locals,
+            depth: self.depth + 1,
+            deadline: self.deadline,
+            max_bits: self.max_bits,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn results_are_reachable_as_ans_and_by_number() {
+        let mut environment = Environment::new();
+
+        assert!(environment.get("ans").is_none());
+        assert_eq!(environment.record(Literal::Integer(4)), 1);
+        assert_eq!(environment.record(Literal::Integer(9)), 2);
+
+        assert!(matches!(environment.get("$1"), Some(Literal::Integer(4))));
+        assert!(matches!(environment.get("ans"), Some(Literal::Integer(9))));
+        assert!(matches!(environment.get("_"), Some(Literal::Integer(9))));
+        assert!(environment.get("$0").is_none());
+        assert!(environment.get("$3").is_none());
+    }
+
+    #[test]
+    fn constants_follow_the_decimal_precision() {
+        let mut environment = Environment::new();
+        environment.set_decimal(true, 40);
+
+        assert_eq!(
+            environment.constant("pi").unwrap().to_string(),
+            "3.141592653589793238462643383279502884197"
+        );
+        assert!(matches!(
+            environment.constant("i"),
+            Some(Literal::Complex(..))
+        ));
+
+        environment.set_decimal(false, 40);
+
+        assert!(matches!(
+            environment.constant("pi"),
+            Some(Literal::Float(..))
+        ));
+    }
+}
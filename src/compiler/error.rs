@@ -0,0 +1,139 @@
+use std::{cmp::Reverse, error::Error, fmt::Display, ops::Range};
+
+#[derive(Debug, Clone)]
+pub enum ScrError {
+    Lexical(String, Range<usize>, Option<String>),
+    Syntax(String, Range<usize>, Option<String>),
+    Runtime(String, Range<usize>, Option<String>),
+}
+
+impl ScrError {
+    pub fn message(&self) -> &str {
+        match self {
+            Self::Lexical(message, ..) | Self::Syntax(message, ..) | Self::Runtime(message, ..) => {
+                message
+            }
+        }
+    }
+
+    pub fn span(&self) -> Range<usize> {
+        match self {
+            Self::Lexical(_, span, _) | Self::Syntax(_, span, _) | Self::Runtime(_, span, _) => {
+                span.clone()
+            }
+        }
+    }
+
+    pub fn hint(&self) -> Option<&str> {
+        match self {
+            Self::Lexical(.., hint) | Self::Syntax(.., hint) | Self::Runtime(.., hint) => {
+                hint.as_deref()
+            }
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Lexical(..) => "lexical error",
+            Self::Syntax(..) => "syntax error",
+            Self::Runtime(..) => "runtime error",
+        }
+    }
+
+    pub fn render(&self, source: &str) -> String {
+        let span = self.span();
+        let start = span.start.min(source.len());
+        let line_start = source[..start].rfind('\n').map_or(0, |index| index + 1);
+        let line_end = source[start..]
+            .find('\n')
+            .map_or(source.len(), |index| start + index);
+        let end = span.end.clamp(start, line_end);
+        let column = source[line_start..start].chars().count();
+        let width = source[start..end].chars().count().max(1);
+
+        let mut rendered = format!(
+            "{self}\n  | {}\n  | {}^{}",
+            &source[line_start..line_end],
+            " ".repeat(column),
+            "~".repeat(width - 1)
+        );
+
+        if let Some(hint) = self.hint() {
+            rendered.push_str(&format!("\n  = hint: {hint}"));
+        }
+
+        rendered
+    }
+}
+
+impl Display for ScrError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.kind(), self.message())
+    }
+}
+
+impl Error for ScrError {}
+
+pub fn suggest<'a>(name: &str, candidates: impl Iterator<Item = &'a str>) -> Option<String> {
+    let threshold = (name.len() / 3).max(1);
+
+    // Ties go to the candidate sharing the longest prefix, then the closest length, so `pii`
+    // suggests `pi` rather than `phi`.
+    candidates
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= threshold && *distance < name.len())
+        .min_by_key(|(distance, candidate)| {
+            (
+                *distance,
+                Reverse(common_prefix(name, candidate)),
+                name.len().abs_diff(candidate.len()),
+                *candidate,
+            )
+        })
+        .map(|(_, candidate)| format!("did you mean `{candidate}`?"))
+}
+
+fn common_prefix(left: &str, right: &str) -> usize {
+    left.chars()
+        .zip(right.chars())
+        .take_while(|(l, r)| l == r)
+        .count()
+}
+
+fn edit_distance(left: &str, right: &str) -> usize {
+    let right = right.chars().collect::<Vec<_>>();
+    let mut previous = (0..=right.len()).collect::<Vec<_>>();
+
+    for (i, l) in left.chars().enumerate() {
+        let mut current = vec![i + 1];
+
+        for (j, r) in right.iter().enumerate() {
+            let substitution = previous[j] + (l != *r) as usize;
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+
+        previous = current;
+    }
+
+    previous[right.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suggestions_prefer_shared_prefixes_on_ties() {
+        let constants = ["e", "phi", "pi", "tau"];
+
+        assert_eq!(
+            suggest("pii", constants.into_iter()).as_deref(),
+            Some("did you mean `pi`?")
+        );
+        assert_eq!(
+            suggest("ph", constants.into_iter()).as_deref(),
+            Some("did you mean `phi`?")
+        );
+        assert_eq!(suggest("xyz", constants.into_iter()), None);
+    }
+}
@@ -0,0 +1,179 @@
+use num_bigint::{BigInt, Sign};
+use num_complex::Complex64;
+use num_rational::BigRational;
+use num_traits::{Signed, Zero};
+
+use super::{decimal::Decimal, environment::AngleUnit};
+
+// The shortest representation of an f64 that round-trips never needs more digits, and more only
+// show noise from its binary representation, so such precisions print that instead.
+const MAX_FLOAT_DIGITS: usize = 17;
+
+#[derive(Clone, Copy)]
+pub struct Format {
+    pub precision: Option<usize>,
+    pub base: u32,
+    pub is_mixed: bool,
+    pub is_polar: bool,
+    pub angle_unit: AngleUnit,
+}
+
+impl Default for Format {
+    fn default() -> Self {
+        Self {
+            precision: None,
+            base: 10,
+            is_mixed: false,
+            is_polar: false,
+            angle_unit: AngleUnit::Radians,
+        }
+    }
+}
+
+impl Format {
+    pub fn integer(&self, value: i128) -> String {
+        let sign = if value < 0 { "-" } else { "" };
+        let magnitude = value.unsigned_abs();
+
+        match self.base {
+            2 => format!("{sign}0b{magnitude:b}"),
+            8 => format!("{sign}0o{magnitude:o}"),
+            16 => format!("{sign}0x{magnitude:x}"),
+            _ => value.to_string(),
+        }
+    }
+
+    pub fn big_integer(&self, value: &BigInt) -> String {
+        let sign = if value.sign() == Sign::Minus { "-" } else { "" };
+        let magnitude = value.magnitude();
+
+        match self.base {
+            2 => format!("{sign}0b{magnitude:b}"),
+            8 => format!("{sign}0o{magnitude:o}"),
+            16 => format!("{sign}0x{magnitude:x}"),
+            _ => value.to_string(),
+        }
+    }
+
+    pub fn rational(&self, value: &BigRational) -> String {
+        let whole = value.trunc().to_integer();
+
+        if !self.is_mixed || whole.is_zero() {
+            return format!(
+                "{}/{}",
+                self.big_integer(value.numer()),
+                self.big_integer(value.denom())
+            );
+        }
+
+        let fraction = value.fract().abs();
+
+        format!(
+            "{} {}/{}",
+            self.big_integer(&whole),
+            self.big_integer(fraction.numer()),
+            self.big_integer(fraction.denom())
+        )
+    }
+
+    // Laid out like `float`, but with every digit the value carries.
+    pub fn decimal(&self, value: &Decimal) -> String {
+        let value = match self.precision {
+            Some(precision) => value.round(precision),
+            None => value.clone(),
+        };
+
+        if value.is_zero() {
+            return "0".to_string();
+        }
+
+        let sign = if value.is_negative() { "-" } else { "" };
+        let digits = value.mantissa().magnitude().to_string();
+        let adjusted = value.adjusted();
+
+        if !(-4..16).contains(&adjusted) {
+            let (leading, rest) = digits.split_at(1);
+
+            return if rest.is_empty() {
+                format!("{sign}{leading}e{adjusted}")
+            } else {
+                format!("{sign}{leading}.{rest}e{adjusted}")
+            };
+        }
+
+        let exponent = value.exponent();
+
+        if exponent >= 0 {
+            return format!("{sign}{digits}{}", "0".repeat(exponent as usize));
+        }
+
+        let point = digits.len() as i64 + exponent;
+
+        if point > 0 {
+            let (whole, fraction) = digits.split_at(point as usize);
+            format!("{sign}{whole}.{fraction}")
+        } else {
+            format!("{sign}0.{}{digits}", "0".repeat(-point as usize))
+        }
+    }
+
+    // Rectangular values read back as input, like `3-4i`; polar ones are shown as `5∠-0.927`.
+    pub fn complex(&self, value: &Complex64) -> String {
+        if self.is_polar {
+            let angle = self.float(self.angle_unit.to_unit(value.arg()));
+            let degrees = if self.angle_unit == AngleUnit::Degrees {
+                "°"
+            } else {
+                ""
+            };
+
+            return format!("{}∠{angle}{degrees}", self.float(value.norm()));
+        }
+
+        let imaginary = format!("{}i", self.float(value.im));
+
+        if value.re == 0.0 {
+            imaginary
+        } else if value.im.is_sign_negative() {
+            format!("{}{imaginary}", self.float(value.re))
+        } else {
+            format!("{}+{imaginary}", self.float(value.re))
+        }
+    }
+
+    pub fn float(&self, value: f64) -> String {
+        let is_scientific = value != 0.0 && !(1e-4..1e16).contains(&value.abs());
+
+        match self
+            .precision
+            .filter(|precision| *precision < MAX_FLOAT_DIGITS)
+        {
+            None if is_scientific => format!("{value:e}"),
+            None => value.to_string(),
+
+            Some(precision) if is_scientific || !value.is_finite() => {
+                format!("{:.*e}", precision - 1, value)
+            }
+
+            Some(precision) => {
+                let magnitude = if value == 0.0 {
+                    0
+                } else {
+                    value.abs().log10().floor() as isize
+                };
+
+                let decimals = (precision as isize - 1 - magnitude).max(0) as usize;
+                let formatted = format!("{value:.decimals$}");
+
+                if formatted.contains('.') {
+                    formatted
+                        .trim_end_matches('0')
+                        .trim_end_matches('.')
+                        .to_string()
+                } else {
+                    formatted
+                }
+            }
+        }
+    }
+}
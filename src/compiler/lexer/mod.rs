@@ -1,5 +1,7 @@
 use token::Token;
 
+use super::error::ScrError;
+
 pub mod token;
 
 pub struct Lexer {
@@ -12,42 +14,56 @@ impl Lexer {
         Self { content, index: 0 }
     }
 
-    pub fn tokenize(&mut self) -> Vec<Token> {
+    pub fn tokenize(&mut self) -> Result<Vec<Token>, ScrError> {
         let mut tokens = Vec::new();
 
-        while self.current().is_some() {
-            tokens.push(self.tokenize_next());
-        }
+        loop {
+            let token = self.tokenize_next()?;
+            let is_end = matches!(token, Token::EndOfLine(..));
+
+            tokens.push(token);
 
-        tokens
+            if is_end {
+                break Ok(tokens);
+            }
+        }
     }
 
-    fn tokenize_next(&mut self) -> Token {
-        match self.skip_whitespace() {
+    fn tokenize_next(&mut self) -> Result<Token, ScrError> {
+        Ok(match self.skip_whitespace() {
             Some(b) if b.is_ascii_alphabetic() || b == b'_' => self.tokenize_identifier(),
-            Some(b) if b.is_ascii_digit() || b == b'.' => self.tokenize_number(),
-            Some(b'+') => self.advance_with_token(Token::Plus(self.index)),
-            Some(b'-') => self.advance_with_token(Token::Minus(self.index)),
-            Some(b'/') => self.advance_with_token(Token::Slash(self.index)),
+            Some(b'$') => self.tokenize_result_reference()?,
+            Some(b'"') => self.tokenize_text()?,
+            Some(b) if b.is_ascii_digit() || b == b'.' => self.tokenize_number()?,
+            Some(b'+') => self.tokenize_plus(),
+            Some(b'-') => self.tokenize_minus(),
+            Some(b'/') => self.tokenize_slash(),
             Some(b'*') => self.tokenize_asterisk(),
-            Some(b'%') => self.advance_with_token(Token::Modulo(self.index)),
+            Some(b'%') => self.tokenize_modulo(),
             Some(b'&') => self.tokenize_ampersand(),
             Some(b'|') => self.tokenize_pipe(),
-            Some(b'^') => self.advance_with_token(Token::Caret(self.index)),
+            Some(b'^') => self.tokenize_caret(),
             Some(b'!') => self.tokenize_exclamation(),
+            Some(b'~') => self.advance_with_token(Token::Tilde(self.index)),
             Some(b'=') => self.tokenize_assign(),
             Some(b'>') => self.tokenize_greater_than(),
             Some(b'<') => self.tokenize_less_than(),
             Some(b'(') => self.advance_with_token(Token::LeftParenthesis(self.index)),
             Some(b')') => self.advance_with_token(Token::RightParenthesis(self.index)),
+            Some(b',') => self.advance_with_token(Token::Comma(self.index)),
+            Some(b';') => self.advance_with_token(Token::Semicolon(self.index)),
             None => Token::EndOfLine(self.index),
 
-            Some(other) => {
-                println!("unhandled character: {}", other as char);
-                self.advance();
-                self.tokenize_next()
+            Some(_) => {
+                let other = self.content[self.index..].chars().next().unwrap();
+
+                return Err(ScrError::Lexical(
+                    format!("unexpected character `{other}`"),
+                    self.index..self.index + other.len_utf8(),
+                    None,
+                ));
             }
-        }
+        })
     }
 
     fn tokenize_identifier(&mut self) -> Token {
@@ -61,10 +77,50 @@ impl Lexer {
             self.advance();
         }
 
-        Token::Identifier(value, index)
+        match value.as_str() {
+            "xor" => Token::Xor(index),
+            _ => Token::Identifier(value, index),
+        }
     }
 
-    fn tokenize_number(&mut self) -> Token {
+    fn tokenize_result_reference(&mut self) -> Result<Token, ScrError> {
+        let index = self.index;
+        let mut value = String::from("$");
+
+        while let Some(b) = self.advance()
+            && b.is_ascii_digit()
+        {
+            value.push(b as char);
+        }
+
+        if value.len() == 1 {
+            return Err(ScrError::Lexical(
+                "expected a result number after `$`".to_string(),
+                index..index + 1,
+                Some("results are referenced as `$1`, `$2`, ...".to_string()),
+            ));
+        }
+
+        Ok(Token::Identifier(value, index))
+    }
+
+    fn tokenize_text(&mut self) -> Result<Token, ScrError> {
+        let index = self.index;
+        let Some(length) = self.content[index + 1..].find('"') else {
+            return Err(ScrError::Lexical(
+                "unterminated string".to_string(),
+                index..self.content.len(),
+                Some("close the string with `\"`".to_string()),
+            ));
+        };
+
+        let value = self.content[index + 1..index + 1 + length].to_string();
+        self.index += length + 2;
+
+        Ok(Token::Text(value, index))
+    }
+
+    fn tokenize_number(&mut self) -> Result<Token, ScrError> {
         let index = self.index;
         let mut value = String::new();
         let mut is_float = false;
@@ -74,8 +130,11 @@ impl Lexer {
         {
             if b == b'.' {
                 if is_float {
-                    println!("syntax error");
-                    return Token::Decimal(String::new(), index);
+                    return Err(ScrError::Lexical(
+                        "number literal has more than one decimal point".to_string(),
+                        index..self.index + 1,
+                        None,
+                    ));
                 } else {
                     is_float = true;
                 }
@@ -85,10 +144,57 @@ impl Lexer {
             self.advance();
         }
 
-        if is_float {
+        if value == "." {
+            return Err(ScrError::Lexical(
+                "expected digits around decimal point".to_string(),
+                index..self.index,
+                None,
+            ));
+        }
+
+        // A trailing `i` makes the number imaginary, as long as it doesn't start a name.
+        if self.current() == Some(b'i')
+            && !self
+                .content
+                .as_bytes()
+                .get(self.index + 1)
+                .is_some_and(|b| b.is_ascii_alphanumeric() || *b == b'_')
+        {
+            self.advance();
+            return Ok(Token::Imaginary(value, index));
+        }
+
+        Ok(if is_float {
             Token::Float(value, index)
         } else {
             Token::Decimal(value, index)
+        })
+    }
+
+    fn tokenize_plus(&mut self) -> Token {
+        let index = self.index;
+
+        match self.advance() {
+            Some(b'=') => self.advance_with_token(Token::PlusAssign(index)),
+            _ => Token::Plus(index),
+        }
+    }
+
+    fn tokenize_minus(&mut self) -> Token {
+        let index = self.index;
+
+        match self.advance() {
+            Some(b'=') => self.advance_with_token(Token::MinusAssign(index)),
+            _ => Token::Minus(index),
+        }
+    }
+
+    fn tokenize_slash(&mut self) -> Token {
+        let index = self.index;
+
+        match self.advance() {
+            Some(b'=') => self.advance_with_token(Token::SlashAssign(index)),
+            _ => Token::Slash(index),
         }
     }
 
@@ -96,16 +202,30 @@ impl Lexer {
         let index = self.index;
 
         match self.advance() {
-            Some(b'*') => self.advance_with_token(Token::AsteriskAsterisk(index)),
+            Some(b'*') => match self.advance() {
+                Some(b'=') => self.advance_with_token(Token::AsteriskAsteriskAssign(index)),
+                _ => Token::AsteriskAsterisk(index),
+            },
+            Some(b'=') => self.advance_with_token(Token::AsteriskAssign(index)),
             _ => Token::Asterisk(index),
         }
     }
 
+    fn tokenize_modulo(&mut self) -> Token {
+        let index = self.index;
+
+        match self.advance() {
+            Some(b'=') => self.advance_with_token(Token::ModuloAssign(index)),
+            _ => Token::Modulo(index),
+        }
+    }
+
     fn tokenize_ampersand(&mut self) -> Token {
         let index = self.index;
 
         match self.advance() {
             Some(b'&') => self.advance_with_token(Token::AmpersandAmpersand(index)),
+            Some(b'=') => self.advance_with_token(Token::AmpersandAssign(index)),
             _ => Token::Ampersand(index),
         }
     }
@@ -115,19 +235,26 @@ impl Lexer {
 
         match self.advance() {
             Some(b'|') => self.advance_with_token(Token::PipePipe(index)),
+            Some(b'=') => self.advance_with_token(Token::PipeAssign(index)),
             _ => Token::Pipe(index),
         }
     }
 
+    fn tokenize_caret(&mut self) -> Token {
+        let index = self.index;
+
+        match self.advance() {
+            Some(b'=') => self.advance_with_token(Token::CaretAssign(index)),
+            _ => Token::Caret(index),
+        }
+    }
+
     fn tokenize_exclamation(&mut self) -> Token {
         let index = self.index;
 
         match self.advance() {
             Some(b'=') => self.advance_with_token(Token::ExclamationAssign(index)),
-            _ => {
-                println!("(!) is unsupported");
-                self.tokenize_next()
-            }
+            _ => Token::Exclamation(index),
         }
     }
 
@@ -144,7 +271,10 @@ impl Lexer {
         let index = self.index;
 
         match self.advance() {
-            Some(b'>') => self.advance_with_token(Token::GreaterThanGreaterThan(index)),
+            Some(b'>') => match self.advance() {
+                Some(b'=') => self.advance_with_token(Token::GreaterThanGreaterThanAssign(index)),
+                _ => Token::GreaterThanGreaterThan(index),
+            },
             Some(b'=') => self.advance_with_token(Token::GreaterThanAssign(index)),
             _ => Token::GreaterThan(index),
         }
@@ -154,7 +284,10 @@ impl Lexer {
         let index = self.index;
 
         match self.advance() {
-            Some(b'<') => self.advance_with_token(Token::LessThanLessThan(index)),
+            Some(b'<') => match self.advance() {
+                Some(b'=') => self.advance_with_token(Token::LessThanLessThanAssign(index)),
+                _ => Token::LessThanLessThan(index),
+            },
             Some(b'=') => self.advance_with_token(Token::LessThanAssign(index)),
             _ => Token::LessThan(index),
         }
@@ -166,11 +299,21 @@ impl Lexer {
     }
 
     fn skip_whitespace(&mut self) -> Option<u8> {
-        while self.current().is_some_and(|b| b.is_ascii_whitespace()) {
-            self.advance();
-        }
+        loop {
+            match self.current() {
+                Some(b) if b.is_ascii_whitespace() => {
+                    self.advance();
+                }
 
-        self.current()
+                Some(b'#') => {
+                    while self.current().is_some_and(|b| b != b'\n') {
+                        self.advance();
+                    }
+                }
+
+                other => break other,
+            }
+        }
     }
 
     fn advance(&mut self) -> Option<u8> {
@@ -179,6 +322,6 @@ impl Lexer {
     }
 
     fn current(&self) -> Option<u8> {
-        self.content.as_bytes().iter().nth(self.index).copied()
+        self.content.as_bytes().get(self.index).copied()
     }
 }
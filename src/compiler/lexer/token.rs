@@ -1,8 +1,12 @@
+use std::{fmt::Display, ops::Range};
+
 #[derive(Debug, Clone)]
 pub enum Token {
     Identifier(String, usize),
     Decimal(String, usize),
     Float(String, usize),
+    Imaginary(String, usize),
+    Text(String, usize),
 
     Plus(usize),
     Minus(usize),
@@ -12,6 +16,8 @@ pub enum Token {
     Ampersand(usize),
     Pipe(usize),
     Caret(usize),
+    Exclamation(usize),
+    Tilde(usize),
     Assign(usize),
     GreaterThan(usize),
     LessThan(usize),
@@ -27,8 +33,176 @@ pub enum Token {
     GreaterThanAssign(usize),
     LessThanAssign(usize),
 
+    PlusAssign(usize),
+    MinusAssign(usize),
+    AsteriskAssign(usize),
+    SlashAssign(usize),
+    ModuloAssign(usize),
+    AmpersandAssign(usize),
+    PipeAssign(usize),
+    CaretAssign(usize),
+
+    AsteriskAsteriskAssign(usize),
+    GreaterThanGreaterThanAssign(usize),
+    LessThanLessThanAssign(usize),
+
+    Xor(usize),
+
     LeftParenthesis(usize),
     RightParenthesis(usize),
+    Comma(usize),
+    Semicolon(usize),
 
     EndOfLine(usize),
 }
+
+impl Token {
+    pub fn index(&self) -> usize {
+        match self {
+            Self::Identifier(_, index)
+            | Self::Decimal(_, index)
+            | Self::Float(_, index)
+            | Self::Imaginary(_, index)
+            | Self::Text(_, index) => *index,
+
+            Self::Plus(index)
+            | Self::Minus(index)
+            | Self::Slash(index)
+            | Self::Asterisk(index)
+            | Self::Modulo(index)
+            | Self::Ampersand(index)
+            | Self::Pipe(index)
+            | Self::Caret(index)
+            | Self::Exclamation(index)
+            | Self::Tilde(index)
+            | Self::Assign(index)
+            | Self::GreaterThan(index)
+            | Self::LessThan(index)
+            | Self::AsteriskAsterisk(index)
+            | Self::AmpersandAmpersand(index)
+            | Self::PipePipe(index)
+            | Self::AssignAssign(index)
+            | Self::GreaterThanGreaterThan(index)
+            | Self::LessThanLessThan(index)
+            | Self::ExclamationAssign(index)
+            | Self::GreaterThanAssign(index)
+            | Self::LessThanAssign(index)
+            | Self::PlusAssign(index)
+            | Self::MinusAssign(index)
+            | Self::AsteriskAssign(index)
+            | Self::SlashAssign(index)
+            | Self::ModuloAssign(index)
+            | Self::AmpersandAssign(index)
+            | Self::PipeAssign(index)
+            | Self::CaretAssign(index)
+            | Self::AsteriskAsteriskAssign(index)
+            | Self::GreaterThanGreaterThanAssign(index)
+            | Self::LessThanLessThanAssign(index)
+            | Self::Xor(index)
+            | Self::LeftParenthesis(index)
+            | Self::RightParenthesis(index)
+            | Self::Comma(index)
+            | Self::Semicolon(index)
+            | Self::EndOfLine(index) => *index,
+        }
+    }
+
+    pub fn span(&self) -> Range<usize> {
+        let index = self.index();
+
+        let len = match self {
+            Self::Identifier(value, ..) | Self::Decimal(value, ..) | Self::Float(value, ..) => {
+                value.len()
+            }
+
+            Self::EndOfLine(..) => 0,
+            other => other.to_string().len(),
+        };
+
+        index..index + len
+    }
+
+    pub fn compound_operator(&self) -> Option<Token> {
+        Some(match self {
+            Self::PlusAssign(index) => Self::Plus(*index),
+            Self::MinusAssign(index) => Self::Minus(*index),
+            Self::AsteriskAssign(index) => Self::Asterisk(*index),
+            Self::SlashAssign(index) => Self::Slash(*index),
+            Self::ModuloAssign(index) => Self::Modulo(*index),
+            Self::AmpersandAssign(index) => Self::Ampersand(*index),
+            Self::PipeAssign(index) => Self::Pipe(*index),
+            Self::CaretAssign(index) => Self::Caret(*index),
+            Self::AsteriskAsteriskAssign(index) => Self::AsteriskAsterisk(*index),
+            Self::GreaterThanGreaterThanAssign(index) => Self::GreaterThanGreaterThan(*index),
+            Self::LessThanLessThanAssign(index) => Self::LessThanLessThan(*index),
+            _ => return None,
+        })
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            Self::EndOfLine(..) => self.to_string(),
+            other => format!("`{other}`"),
+        }
+    }
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Identifier(value, ..) | Self::Decimal(value, ..) | Self::Float(value, ..) => {
+                write!(f, "{value}")
+            }
+
+            Self::Imaginary(value, ..) => write!(f, "{value}i"),
+            Self::Text(value, ..) => write!(f, "\"{value}\""),
+
+            Self::Plus(..) => write!(f, "+"),
+            Self::Minus(..) => write!(f, "-"),
+            Self::Slash(..) => write!(f, "/"),
+            Self::Asterisk(..) => write!(f, "*"),
+            Self::Modulo(..) => write!(f, "%"),
+            Self::Ampersand(..) => write!(f, "&"),
+            Self::Pipe(..) => write!(f, "|"),
+            Self::Caret(..) => write!(f, "^"),
+            Self::Exclamation(..) => write!(f, "!"),
+            Self::Tilde(..) => write!(f, "~"),
+            Self::Assign(..) => write!(f, "="),
+            Self::GreaterThan(..) => write!(f, ">"),
+            Self::LessThan(..) => write!(f, "<"),
+
+            Self::AsteriskAsterisk(..) => write!(f, "**"),
+            Self::AmpersandAmpersand(..) => write!(f, "&&"),
+            Self::PipePipe(..) => write!(f, "||"),
+            Self::AssignAssign(..) => write!(f, "=="),
+            Self::GreaterThanGreaterThan(..) => write!(f, ">>"),
+            Self::LessThanLessThan(..) => write!(f, "<<"),
+
+            Self::ExclamationAssign(..) => write!(f, "!="),
+            Self::GreaterThanAssign(..) => write!(f, ">="),
+            Self::LessThanAssign(..) => write!(f, "<="),
+
+            Self::PlusAssign(..) => write!(f, "+="),
+            Self::MinusAssign(..) => write!(f, "-="),
+            Self::AsteriskAssign(..) => write!(f, "*="),
+            Self::SlashAssign(..) => write!(f, "/="),
+            Self::ModuloAssign(..) => write!(f, "%="),
+            Self::AmpersandAssign(..) => write!(f, "&="),
+            Self::PipeAssign(..) => write!(f, "|="),
+            Self::CaretAssign(..) => write!(f, "^="),
+
+            Self::AsteriskAsteriskAssign(..) => write!(f, "**="),
+            Self::GreaterThanGreaterThanAssign(..) => write!(f, ">>="),
+            Self::LessThanLessThanAssign(..) => write!(f, "<<="),
+
+            Self::Xor(..) => write!(f, "xor"),
+
+            Self::LeftParenthesis(..) => write!(f, "("),
+            Self::RightParenthesis(..) => write!(f, ")"),
+            Self::Comma(..) => write!(f, ","),
+            Self::Semicolon(..) => write!(f, ";"),
+
+            Self::EndOfLine(..) => write!(f, "end of line"),
+        }
+    }
+}
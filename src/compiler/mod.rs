@@ -1,2 +1,8 @@
+pub mod builtins;
+pub mod constants;
+pub mod decimal;
+pub mod environment;
+pub mod error;
+pub mod format;
 pub mod lexer;
 pub mod parser;
@@ -1,46 +1,502 @@
-use std::{collections::HashMap, fmt::Display};
+use std::{collections::BTreeMap, fmt::Display, ops::Range, time::Instant};
 
-use crate::compiler::lexer::token::Token;
+use num_bigint::BigInt;
+use num_complex::Complex64;
+use num_integer::Integer;
+use num_rational::BigRational;
+use num_traits::{Signed, ToPrimitive, Zero};
+
+use crate::compiler::{
+    builtins::{self, Angular, ComplexFunction},
+    decimal::{Decimal, GUARD_DIGITS},
+    environment::{AngleUnit, Environment, Scope},
+    error::{ScrError, suggest},
+    format::Format,
+    lexer::token::Token,
+};
+
+use super::precedence::{self, Associativity, PREFIX_PRECEDENCE};
 
 #[derive(Clone)]
 pub enum Literal {
     Name(String),
-    Integer(usize),
+    Integer(i128),
+    BigInteger(BigInt),
+    Rational(BigRational),
+    Decimal(Decimal),
     Float(f64),
+    Complex(Complex64),
 }
 
-impl Display for Literal {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl Literal {
+    pub fn from_big(value: BigInt) -> Self {
+        match i128::try_from(&value) {
+            Ok(value) => Self::Integer(value),
+            Err(_) => Self::BigInteger(value),
+        }
+    }
+
+    pub fn from_ratio(value: BigRational) -> Self {
+        if value.is_integer() {
+            Self::from_big(value.to_integer())
+        } else {
+            Self::Rational(value)
+        }
+    }
+
+    // Values on the real axis are plain floats again.
+    pub fn from_complex(value: Complex64) -> Self {
+        if value.im == 0.0 {
+            Self::Float(value.re)
+        } else {
+            Self::Complex(value)
+        }
+    }
+
+    pub fn to_big(&self) -> Option<BigInt> {
+        match self {
+            Self::Integer(value) => Some(BigInt::from(*value)),
+            Self::BigInteger(value) => Some(value.clone()),
+            _ => None,
+        }
+    }
+
+    pub fn to_ratio(&self) -> Option<BigRational> {
+        match self {
+            Self::Rational(value) => Some(value.clone()),
+            other => other.to_big().map(BigRational::from_integer),
+        }
+    }
+
+    pub fn to_decimal(&self, precision: usize) -> Option<Decimal> {
+        match self {
+            Self::Integer(value) => Some(Decimal::from_big(BigInt::from(*value))),
+            Self::BigInteger(value) => Some(Decimal::from_big(value.clone())),
+            Self::Rational(value) => Some(Decimal::from_ratio(value, precision)),
+            Self::Decimal(value) => Some(value.clone()),
+            Self::Float(value) => Decimal::from_f64(*value),
+            Self::Name(..) | Self::Complex(..) => None,
+        }
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        match self {
+            Self::Integer(value) => *value as f64,
+            Self::BigInteger(value) => value.to_f64().unwrap_or(f64::NAN),
+            Self::Rational(value) => value.to_f64().unwrap_or(f64::NAN),
+            Self::Decimal(value) => value.to_f64(),
+            Self::Float(value) => *value,
+            Self::Complex(value) if value.im == 0.0 => value.re,
+            Self::Complex(..) => f64::NAN,
+            Self::Name(..) => unreachable!(),
+        }
+    }
+
+    // The size of the value's digits, which is what the cost of arithmetic on it depends on.
+    pub fn bits(&self) -> u64 {
         match self {
-            Self::Name(name) => write!(f, "{name}"),
-            Self::Integer(value) => write!(f, "{value}"),
-            Self::Float(value) => write!(f, "{value}"),
+            Self::Integer(value) => u64::from(i128::BITS - value.unsigned_abs().leading_zeros()),
+            Self::BigInteger(value) => value.bits(),
+            Self::Rational(value) => value.numer().bits().max(value.denom().bits()),
+            Self::Decimal(value) => value.mantissa().bits(),
+            Self::Float(..) | Self::Complex(..) => 64,
+            Self::Name(..) => 0,
+        }
+    }
+
+    pub fn to_complex(&self) -> Complex64 {
+        match self {
+            Self::Complex(value) => *value,
+            other => Complex64::new(other.to_f64(), 0.0),
+        }
+    }
+
+    pub fn format(&self, format: &Format) -> String {
+        match self {
+            Self::Name(name) => name.clone(),
+            Self::Integer(value) => format.integer(*value),
+            Self::BigInteger(value) => format.big_integer(value),
+            Self::Rational(value) => format.rational(value),
+            Self::Decimal(value) => format.decimal(value),
+            Self::Float(value) => format.float(*value),
+            Self::Complex(value) => format.complex(value),
         }
     }
 }
 
+impl Display for Literal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.format(&Format::default()))
+    }
+}
+
+#[derive(Clone)]
 pub enum Expression {
+    Unary(Token, Box<Expression>),
     Binary(Box<Expression>, Token, Box<Expression>),
+    Call(String, Vec<Expression>, usize),
     Literal(Literal, usize),
 }
 
 impl Expression {
-    pub fn evaluate(&self, variables: &HashMap<String, Literal>) -> Literal {
+    pub fn evaluate(&self, environment: &Environment) -> Result<Literal, ScrError> {
+        self.evaluate_in(&Scope::new(environment))
+    }
+
+    // Bounded evaluation gives up once the deadline passes or before any operation on values
+    // larger than `max_bits`, since a single operation on huge numbers can take seconds.
+    pub fn evaluate_until(
+        &self,
+        environment: &Environment,
+        deadline: Instant,
+        max_bits: u64,
+    ) -> Result<Literal, ScrError> {
+        self.evaluate_in(
+            &Scope::new(environment)
+                .with_deadline(deadline)
+                .with_max_bits(max_bits),
+        )
+    }
+
+    fn evaluate_in(&self, scope: &Scope) -> Result<Literal, ScrError> {
         match self {
-            Self::Binary(..) => self.evaluate_binary(variables),
+            Self::Unary(..) => self.evaluate_unary(scope),
+            Self::Binary(..) => self.evaluate_binary(scope),
+            Self::Call(..) => self.evaluate_call(scope),
 
-            Self::Literal(literal, ..) => match literal {
-                Literal::Name(name) => {
-                    if let Some(value) = variables.get(name) {
-                        value.clone()
-                    } else {
-                        println!("undefined name: {name}");
-                        Literal::Integer(0)
+            Self::Literal(literal, index) => match literal {
+                Literal::Name(name) => scope.get(name).cloned().ok_or_else(|| {
+                    ScrError::Runtime(
+                        format!("undefined name `{name}`"),
+                        *index..*index + name.len(),
+                        suggest(name, scope.names()),
+                    )
+                }),
+
+                Literal::Decimal(value) if scope.environment().is_decimal() => Ok(
+                    Literal::Decimal(value.round(scope.environment().precision())),
+                ),
+
+                Literal::Decimal(value) => Ok(Literal::Float(value.to_f64())),
+                Literal::Complex(value) => Ok(Literal::from_complex(*value)),
+                other => Ok(other.clone()),
+            },
+        }
+    }
+
+    fn evaluate_unary(&self, scope: &Scope) -> Result<Literal, ScrError> {
+        let Self::Unary(operator, operand) = self else {
+            unreachable!()
+        };
+
+        let operand = operand.evaluate_in(scope)?;
+
+        Ok(match operator {
+            Token::Plus(..) => operand,
+
+            Token::Minus(..) => match operand {
+                Literal::Integer(value) => match value.checked_neg() {
+                    Some(value) => Literal::Integer(value),
+                    None => Literal::BigInteger(-BigInt::from(value)),
+                },
+                Literal::BigInteger(value) => Literal::from_big(-value),
+                Literal::Rational(value) => Literal::Rational(-value),
+                Literal::Decimal(value) => Literal::Decimal(value.neg()),
+                Literal::Float(value) => Literal::Float(-value),
+                Literal::Complex(value) => Literal::Complex(-value),
+                _ => unreachable!(),
+            },
+
+            Token::Exclamation(..) => match operand {
+                Literal::Integer(value) => Literal::Integer((value == 0) as i128),
+                Literal::BigInteger(value) => Literal::Integer(value.is_zero() as i128),
+                Literal::Rational(value) => Literal::Integer(value.is_zero() as i128),
+                Literal::Decimal(value) => Literal::Integer(value.is_zero() as i128),
+                Literal::Float(value) => Literal::Integer((value == 0.0) as i128),
+                Literal::Complex(value) => Literal::Integer(value.is_zero() as i128),
+                _ => unreachable!(),
+            },
+
+            Token::Tilde(..) => match operand {
+                Literal::Integer(value) => Literal::Integer(!value),
+                Literal::BigInteger(value) => Literal::from_big(!value),
+
+                _ => {
+                    return Err(ScrError::Runtime(
+                        "cannot perform bitwise not (~) on non-integer literals".to_string(),
+                        operator.span(),
+                        None,
+                    ));
+                }
+            },
+
+            _ => {
+                return Err(ScrError::Runtime(
+                    format!("unsupported unary operator `{operator}`"),
+                    operator.span(),
+                    None,
+                ));
+            }
+        })
+    }
+
+    fn evaluate_call(&self, scope: &Scope) -> Result<Literal, ScrError> {
+        let Self::Call(name, arguments, index) = self else {
+            unreachable!()
+        };
+
+        let span = *index..*index + name.len();
+
+        if name == "if" {
+            return self.evaluate_if(scope);
+        }
+
+        if let Some(function) = scope.environment().function(name) {
+            if scope.is_expired() {
+                return Err(ScrError::Runtime(
+                    "evaluation timed out".to_string(),
+                    span,
+                    None,
+                ));
+            }
+
+            if arguments.len() != function.parameters.len() {
+                return Err(ScrError::Runtime(
+                    format!(
+                        "`{name}` takes {} argument(s) but {} were given",
+                        function.parameters.len(),
+                        arguments.len()
+                    ),
+                    span,
+                    Some(format!("usage: {}", function.signature(name))),
+                ));
+            }
+
+            let locals = function
+                .parameters
+                .iter()
+                .cloned()
+                .zip(
+                    arguments
+                        .iter()
+                        .map(|argument| argument.evaluate_in(scope))
+                        .collect::<Result<Vec<_>, _>>()?,
+                )
+                .collect::<BTreeMap<_, _>>();
+
+            let Some(inner) = scope.enter(locals) else {
+                return Err(ScrError::Runtime(
+                    format!(
+                        "maximum recursion depth of {} exceeded",
+                        scope.environment().max_depth()
+                    ),
+                    span,
+                    Some("the limit can be changed with `set depth <n>`".to_string()),
+                ));
+            };
+
+            // The body was parsed from the line that defined the function, so its spans are
+            // meaningless here; errors are reported at the outermost call site instead.
+            return function.body.evaluate_in(&inner).map_err(|error| {
+                if scope.depth() == 0 {
+                    ScrError::Runtime(
+                        format!("in `{name}`: {}", error.message()),
+                        span.clone(),
+                        error.hint().map(str::to_string),
+                    )
+                } else {
+                    error
+                }
+            });
+        }
+
+        if let Some(native) = scope.environment().native_function(name) {
+            if arguments.len() != native.parameters.len() {
+                return Err(ScrError::Runtime(
+                    format!(
+                        "`{name}` takes {} argument(s) but {} were given",
+                        native.parameters.len(),
+                        arguments.len()
+                    ),
+                    span,
+                    Some(format!("usage: {}", native.signature(name))),
+                ));
+            }
+
+            let arguments = arguments
+                .iter()
+                .map(|argument| argument.evaluate_in(scope))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            check_bounds(scope, &arguments, span.clone())?;
+
+            return (native.function)(&arguments)
+                .map_err(|message| ScrError::Runtime(message, span, None));
+        }
+
+        let Some(builtin) = builtins::lookup(name) else {
+            let environment = scope.environment();
+
+            return Err(ScrError::Runtime(
+                format!("undefined function `{name}`"),
+                span,
+                suggest(
+                    name,
+                    builtins::BUILTINS
+                        .iter()
+                        .map(|builtin| builtin.name)
+                        .chain(environment.functions().map(|(name, _)| name.as_str()))
+                        .chain(
+                            environment
+                                .native_functions()
+                                .map(|(name, _)| name.as_str()),
+                        ),
+                ),
+            ));
+        };
+
+        if !builtin.accepts(arguments.len()) {
+            return Err(ScrError::Runtime(
+                format!(
+                    "`{name}` takes {}{} argument(s) but {} were given",
+                    if builtin.variadic { "at least " } else { "" },
+                    builtin.parameters.len(),
+                    arguments.len()
+                ),
+                span,
+                Some(format!("usage: {}", builtin.signature())),
+            ));
+        }
+
+        let environment = scope.environment();
+        let angle_unit = environment.angle_unit();
+        let precision = environment.precision();
+
+        let arguments = arguments
+            .iter()
+            .map(|argument| argument.evaluate_in(scope))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        check_bounds(scope, &arguments, span.clone())?;
+
+        if arguments
+            .iter()
+            .any(|argument| matches!(argument, Literal::Complex(_)))
+        {
+            let Some(function) = builtin.complex else {
+                return Err(ScrError::Runtime(
+                    format!("`{name}` is not defined for complex numbers"),
+                    span,
+                    None,
+                ));
+            };
+
+            return Ok(Literal::from_complex(evaluate_complex(
+                function,
+                &builtin.angular,
+                &arguments,
+                angle_unit,
+            )));
+        }
+
+        // Real arguments outside of a function's domain, like `sqrt(-1)`, give its principal
+        // complex value instead, unless that isn't finite either.
+        let complex = || {
+            builtin
+                .complex
+                .map(|function| {
+                    evaluate_complex(function, &builtin.angular, &arguments, angle_unit)
+                })
+                .filter(|result| result.is_finite())
+                .map(Literal::from_complex)
+        };
+
+        if let Some(function) = builtin.decimal
+            && (environment.is_decimal()
+                || arguments
+                    .iter()
+                    .any(|argument| matches!(argument, Literal::Decimal(_))))
+            && let Some(arguments) = arguments
+                .iter()
+                .map(|argument| argument.to_decimal(precision))
+                .collect::<Option<Vec<_>>>()
+        {
+            // Converting angles costs a digit or two, so the call is made with guard digits.
+            let working = precision + GUARD_DIGITS;
+            let count = arguments.len();
+
+            let arguments = arguments
+                .into_iter()
+                .enumerate()
+                .map(|(index, argument)| match builtin.angular {
+                    Angular::Argument if index + 1 == count => {
+                        angle_unit.decimal_to_radians(&argument, working)
                     }
+                    _ => argument,
+                })
+                .collect::<Vec<_>>();
+
+            let result = match function(&arguments, working) {
+                Ok(result) => result,
+                Err(message) => return complex().ok_or(ScrError::Runtime(message, span, None)),
+            };
+
+            let result = match builtin.angular {
+                Angular::Result => angle_unit.decimal_to_unit(&result, working),
+                _ => result,
+            };
+
+            return Ok(Literal::Decimal(result.round(precision)));
+        }
+
+        let real = arguments
+            .iter()
+            .enumerate()
+            .map(|(index, argument)| match builtin.angular {
+                Angular::Argument if index + 1 == arguments.len() => {
+                    Literal::Float(angle_unit.to_radians(argument.to_f64()))
                 }
+                _ => argument.clone(),
+            })
+            .collect::<Vec<_>>();
 
-                other => other.clone(),
-            },
+        let result =
+            (builtin.function)(&real).map_err(|message| ScrError::Runtime(message, span, None))?;
+
+        if let Literal::Float(value) = result
+            && value.is_nan()
+            && !arguments.iter().any(|argument| argument.to_f64().is_nan())
+            && let Some(result) = complex()
+        {
+            return Ok(result);
+        }
+
+        Ok(match builtin.angular {
+            Angular::Result => Literal::Float(angle_unit.to_unit(result.to_f64())),
+            _ => result,
+        })
+    }
+
+    fn evaluate_if(&self, scope: &Scope) -> Result<Literal, ScrError> {
+        let Self::Call(name, arguments, index) = self else {
+            unreachable!()
+        };
+
+        let [condition, then, otherwise] = arguments.as_slice() else {
+            return Err(ScrError::Runtime(
+                format!(
+                    "`if` takes 3 argument(s) but {} were given",
+                    arguments.len()
+                ),
+                *index..*index + name.len(),
+                Some("usage: if(condition, then, otherwise)".to_string()),
+            ));
+        };
+
+        if condition.evaluate_in(scope)?.to_f64() != 0.0 {
+            then.evaluate_in(scope)
+        } else {
+            otherwise.evaluate_in(scope)
         }
     }
 
@@ -49,10 +505,16 @@ impl Expression {
         left: &Literal,
         operator: &Token,
         right: &Literal,
-    ) -> Literal {
-        match operator {
+    ) -> Result<Literal, ScrError> {
+        // Machine integers that overflow are promoted to big integers.
+        let promote = || self.evaluate_binary_big(left, operator, right);
+
+        Ok(match operator {
             Token::Plus(..) => match (left, right) {
-                (Literal::Integer(left), Literal::Integer(right)) => Literal::Integer(left + right),
+                (Literal::Integer(left), Literal::Integer(right)) => left
+                    .checked_add(*right)
+                    .map(Literal::Integer)
+                    .map_or_else(promote, Ok)?,
                 (Literal::Integer(left), Literal::Float(right)) => {
                     Literal::Float(*left as f64 + right)
                 }
@@ -64,7 +526,10 @@ impl Expression {
             },
 
             Token::Minus(..) => match (left, right) {
-                (Literal::Integer(left), Literal::Integer(right)) => Literal::Integer(left - right),
+                (Literal::Integer(left), Literal::Integer(right)) => left
+                    .checked_sub(*right)
+                    .map(Literal::Integer)
+                    .map_or_else(promote, Ok)?,
                 (Literal::Integer(left), Literal::Float(right)) => {
                     Literal::Float(*left as f64 - right)
                 }
@@ -76,7 +541,10 @@ impl Expression {
             },
 
             Token::Asterisk(..) => match (left, right) {
-                (Literal::Integer(left), Literal::Integer(right)) => Literal::Integer(left * right),
+                (Literal::Integer(left), Literal::Integer(right)) => left
+                    .checked_mul(*right)
+                    .map(Literal::Integer)
+                    .map_or_else(promote, Ok)?,
                 (Literal::Integer(left), Literal::Float(right)) => {
                     Literal::Float(*left as f64 * right)
                 }
@@ -101,15 +569,35 @@ impl Expression {
                 _ => unreachable!(),
             },
 
+            // `%` follows the sign of the divisor (floored division), so `-7 % 3` is `2`.
             Token::Modulo(..) => match (left, right) {
-                (Literal::Integer(left), Literal::Integer(right)) => Literal::Integer(left % right),
+                (Literal::Integer(_), Literal::Integer(0)) => {
+                    return Err(ScrError::Runtime(
+                        "division by zero".to_string(),
+                        operator.span(),
+                        None,
+                    ));
+                }
+                (Literal::Integer(left), Literal::Integer(right)) => {
+                    let Some(remainder) = left.checked_rem(*right) else {
+                        return promote();
+                    };
+
+                    if remainder != 0 && (remainder < 0) != (*right < 0) {
+                        Literal::Integer(remainder + right)
+                    } else {
+                        Literal::Integer(remainder)
+                    }
+                }
                 (Literal::Integer(left), Literal::Float(right)) => {
-                    Literal::Float(*left as f64 % right)
+                    Literal::Float(floored_remainder(*left as f64, *right))
                 }
                 (Literal::Float(left), Literal::Integer(right)) => {
-                    Literal::Float(left % *right as f64)
+                    Literal::Float(floored_remainder(*left, *right as f64))
+                }
+                (Literal::Float(left), Literal::Float(right)) => {
+                    Literal::Float(floored_remainder(*left, *right))
                 }
-                (Literal::Float(left), Literal::Float(right)) => Literal::Float(left % right),
                 _ => unreachable!(),
             },
 
@@ -117,8 +605,12 @@ impl Expression {
                 (Literal::Integer(left), Literal::Integer(right)) => Literal::Integer(left | right),
 
                 _ => {
-                    println!("cannot perform bitwise inclusive or (|) on non-integer literals");
-                    Literal::Integer(0)
+                    return Err(ScrError::Runtime(
+                        "cannot perform bitwise inclusive or (|) on non-integer literals"
+                            .to_string(),
+                        operator.span(),
+                        None,
+                    ));
                 }
             },
 
@@ -126,56 +618,68 @@ impl Expression {
                 (Literal::Integer(left), Literal::Integer(right)) => Literal::Integer(left & right),
 
                 _ => {
-                    println!("cannot perform bitwise and (&) on non-integer literals");
-                    Literal::Integer(0)
+                    return Err(ScrError::Runtime(
+                        "cannot perform bitwise and (&) on non-integer literals".to_string(),
+                        operator.span(),
+                        None,
+                    ));
                 }
             },
 
-            Token::Caret(..) => match (left, right) {
+            Token::Caret(..) | Token::Xor(..) => match (left, right) {
                 (Literal::Integer(left), Literal::Integer(right)) => Literal::Integer(left ^ right),
 
                 _ => {
-                    println!("cannot perform bitwise exclusive or (^) on non-integer literals");
-                    Literal::Integer(0)
+                    return Err(ScrError::Runtime(
+                        "cannot perform bitwise exclusive or (^) on non-integer literals"
+                            .to_string(),
+                        operator.span(),
+                        None,
+                    ));
                 }
             },
 
             Token::GreaterThan(..) => match (left, right) {
                 (Literal::Integer(left), Literal::Integer(right)) => {
-                    Literal::Integer((left > right) as usize)
+                    Literal::Integer((left > right) as i128)
                 }
                 (Literal::Integer(left), Literal::Float(right)) => {
-                    Literal::Float((*left as f64 > *right) as usize as f64)
+                    Literal::Integer((*left as f64 > *right) as i128)
                 }
                 (Literal::Float(left), Literal::Integer(right)) => {
-                    Literal::Float((*left > *right as f64) as usize as f64)
+                    Literal::Integer((*left > *right as f64) as i128)
                 }
                 (Literal::Float(left), Literal::Float(right)) => {
-                    Literal::Float((left > right) as usize as f64)
+                    Literal::Integer((left > right) as i128)
                 }
                 _ => unreachable!(),
             },
 
             Token::LessThan(..) => match (left, right) {
                 (Literal::Integer(left), Literal::Integer(right)) => {
-                    Literal::Integer((left < right) as usize)
+                    Literal::Integer((left < right) as i128)
                 }
                 (Literal::Integer(left), Literal::Float(right)) => {
-                    Literal::Float(((*left as f64) < *right) as usize as f64)
+                    Literal::Integer(((*left as f64) < *right) as i128)
                 }
                 (Literal::Float(left), Literal::Integer(right)) => {
-                    Literal::Float((*left < *right as f64) as usize as f64)
+                    Literal::Integer((*left < *right as f64) as i128)
                 }
                 (Literal::Float(left), Literal::Float(right)) => {
-                    Literal::Float((left < right) as usize as f64)
+                    Literal::Integer((left < right) as i128)
                 }
                 _ => unreachable!(),
             },
 
             Token::AsteriskAsterisk(..) => match (left, right) {
-                (Literal::Integer(left), Literal::Integer(right)) => {
-                    Literal::Integer(left.pow(*right as u32))
+                (Literal::Integer(left), Literal::Integer(right)) if *right < 0 => {
+                    Literal::Float((*left as f64).powf(*right as f64))
                 }
+                (Literal::Integer(left), Literal::Integer(right)) => u32::try_from(*right)
+                    .ok()
+                    .and_then(|exponent| left.checked_pow(exponent))
+                    .map(Literal::Integer)
+                    .map_or_else(promote, Ok)?,
                 (Literal::Integer(left), Literal::Float(right)) => {
                     Literal::Float((*left as f64).powf(*right))
                 }
@@ -188,135 +692,840 @@ impl Expression {
 
             Token::AmpersandAmpersand(..) => match (left, right) {
                 (Literal::Integer(left), Literal::Integer(right)) => {
-                    Literal::Integer(((*left > 0) && (*right > 0)) as usize)
+                    Literal::Integer(((*left != 0) && (*right != 0)) as i128)
                 }
                 (Literal::Integer(left), Literal::Float(right)) => {
-                    Literal::Float(((*left > 0) && (*right > 0.0)) as usize as f64)
+                    Literal::Integer(((*left != 0) && (*right != 0.0)) as i128)
                 }
                 (Literal::Float(left), Literal::Integer(right)) => {
-                    Literal::Float(((*left > 0.0) && (*right > 0)) as usize as f64)
+                    Literal::Integer(((*left != 0.0) && (*right != 0)) as i128)
                 }
                 (Literal::Float(left), Literal::Float(right)) => {
-                    Literal::Float(((*left > 0.0) && (*right > 0.0)) as usize as f64)
+                    Literal::Integer(((*left != 0.0) && (*right != 0.0)) as i128)
                 }
                 _ => unreachable!(),
             },
 
             Token::PipePipe(..) => match (left, right) {
                 (Literal::Integer(left), Literal::Integer(right)) => {
-                    Literal::Integer(((*left > 0) || (*right > 0)) as usize)
+                    Literal::Integer(((*left != 0) || (*right != 0)) as i128)
                 }
                 (Literal::Integer(left), Literal::Float(right)) => {
-                    Literal::Float(((*left > 0) || (*right > 0.0)) as usize as f64)
+                    Literal::Integer(((*left != 0) || (*right != 0.0)) as i128)
                 }
                 (Literal::Float(left), Literal::Integer(right)) => {
-                    Literal::Float(((*left > 0.0) || (*right > 0)) as usize as f64)
+                    Literal::Integer(((*left != 0.0) || (*right != 0)) as i128)
                 }
                 (Literal::Float(left), Literal::Float(right)) => {
-                    Literal::Float(((*left > 0.0) || (*right > 0.0)) as usize as f64)
+                    Literal::Integer(((*left != 0.0) || (*right != 0.0)) as i128)
                 }
                 _ => unreachable!(),
             },
 
             Token::AssignAssign(..) => match (left, right) {
                 (Literal::Integer(left), Literal::Integer(right)) => {
-                    Literal::Integer((left == right) as usize)
+                    Literal::Integer((left == right) as i128)
                 }
                 (Literal::Integer(left), Literal::Float(right)) => {
-                    Literal::Float((*left as f64 == *right) as usize as f64)
+                    Literal::Integer((*left as f64 == *right) as i128)
                 }
                 (Literal::Float(left), Literal::Integer(right)) => {
-                    Literal::Float((*left == *right as f64) as usize as f64)
+                    Literal::Integer((*left == *right as f64) as i128)
                 }
                 (Literal::Float(left), Literal::Float(right)) => {
-                    Literal::Float((left == right) as usize as f64)
+                    Literal::Integer((left == right) as i128)
                 }
                 _ => unreachable!(),
             },
 
+            // `>>` is an arithmetic shift, so negative values keep their sign.
             Token::GreaterThanGreaterThan(..) => match (left, right) {
                 (Literal::Integer(left), Literal::Integer(right)) => {
-                    Literal::Integer(left >> right)
+                    let amount = shift_amount(*right, operator)?;
+                    Literal::Integer(left >> amount.min(i128::BITS - 1))
                 }
 
                 _ => {
-                    println!("cannot perform bitwise shift (>>) on non-integer literals");
-                    Literal::Integer(0)
+                    return Err(ScrError::Runtime(
+                        "cannot perform bitwise shift (>>) on non-integer literals".to_string(),
+                        operator.span(),
+                        None,
+                    ));
                 }
             },
 
             Token::LessThanLessThan(..) => match (left, right) {
                 (Literal::Integer(left), Literal::Integer(right)) => {
-                    Literal::Integer(left << right)
+                    let amount = shift_amount(*right, operator)?;
+
+                    match left.checked_shl(amount) {
+                        Some(shifted) if shifted >> amount == *left => Literal::Integer(shifted),
+                        _ => promote()?,
+                    }
                 }
 
                 _ => {
-                    println!("cannot perform bitwise shift (>>) on non-integer literals");
-                    Literal::Integer(0)
+                    return Err(ScrError::Runtime(
+                        "cannot perform bitwise shift (<<) on non-integer literals".to_string(),
+                        operator.span(),
+                        None,
+                    ));
                 }
             },
 
             Token::ExclamationAssign(..) => match (left, right) {
                 (Literal::Integer(left), Literal::Integer(right)) => {
-                    Literal::Integer((left != right) as usize)
+                    Literal::Integer((left != right) as i128)
                 }
                 (Literal::Integer(left), Literal::Float(right)) => {
-                    Literal::Float((*left as f64 != *right) as usize as f64)
+                    Literal::Integer((*left as f64 != *right) as i128)
                 }
                 (Literal::Float(left), Literal::Integer(right)) => {
-                    Literal::Float((*left != *right as f64) as usize as f64)
+                    Literal::Integer((*left != *right as f64) as i128)
                 }
                 (Literal::Float(left), Literal::Float(right)) => {
-                    Literal::Float((left != right) as usize as f64)
+                    Literal::Integer((left != right) as i128)
                 }
                 _ => unreachable!(),
             },
 
             Token::GreaterThanAssign(..) => match (left, right) {
                 (Literal::Integer(left), Literal::Integer(right)) => {
-                    Literal::Integer((left >= right) as usize)
+                    Literal::Integer((left >= right) as i128)
                 }
                 (Literal::Integer(left), Literal::Float(right)) => {
-                    Literal::Float((*left as f64 >= *right) as usize as f64)
+                    Literal::Integer((*left as f64 >= *right) as i128)
                 }
                 (Literal::Float(left), Literal::Integer(right)) => {
-                    Literal::Float((*left >= *right as f64) as usize as f64)
+                    Literal::Integer((*left >= *right as f64) as i128)
                 }
                 (Literal::Float(left), Literal::Float(right)) => {
-                    Literal::Float((left >= right) as usize as f64)
+                    Literal::Integer((left >= right) as i128)
                 }
                 _ => unreachable!(),
             },
 
             Token::LessThanAssign(..) => match (left, right) {
                 (Literal::Integer(left), Literal::Integer(right)) => {
-                    Literal::Integer((left <= right) as usize)
+                    Literal::Integer((left <= right) as i128)
                 }
                 (Literal::Integer(left), Literal::Float(right)) => {
-                    Literal::Float((*left as f64 <= *right) as usize as f64)
+                    Literal::Integer((*left as f64 <= *right) as i128)
                 }
                 (Literal::Float(left), Literal::Integer(right)) => {
-                    Literal::Float((*left <= *right as f64) as usize as f64)
+                    Literal::Integer((*left <= *right as f64) as i128)
                 }
                 (Literal::Float(left), Literal::Float(right)) => {
-                    Literal::Float((left <= right) as usize as f64)
+                    Literal::Integer((left <= right) as i128)
                 }
                 _ => unreachable!(),
             },
 
-            _ => Literal::Integer(0),
-        }
+            _ => {
+                return Err(ScrError::Runtime(
+                    format!("unsupported binary operator `{operator}`"),
+                    operator.span(),
+                    None,
+                ));
+            }
+        })
     }
 
-    fn evaluate_binary(&self, variables: &HashMap<String, Literal>) -> Literal {
+    fn evaluate_binary(&self, scope: &Scope) -> Result<Literal, ScrError> {
         let Self::Binary(left, operator, right) = self else {
             unreachable!()
         };
 
-        let left = left.evaluate(variables);
-        let right = right.evaluate(variables);
-        let result = self.evaluate_binary_integers(&left, operator, &right);
+        let left = left.evaluate_in(scope)?;
+        let right = right.evaluate_in(scope)?;
+
+        check_bounds(scope, [&left, &right], operator.span())?;
+
+        let environment = scope.environment();
+        let precision = environment.is_decimal().then(|| environment.precision());
+
+        // Integer division and negative powers produce rationals in exact mode and decimals in
+        // decimal mode instead of floats.
+        let is_inexact = match operator {
+            Token::Slash(..) => true,
+            Token::AsteriskAsterisk(..) => right.to_f64() < 0.0,
+            _ => false,
+        };
+
+        // Negative bases only have complex fractional powers, like `(-8) ** (1/3)`.
+        let is_complex_power = matches!(operator, Token::AsteriskAsterisk(..))
+            && left.to_f64() < 0.0
+            && right.to_f64().fract() != 0.0
+            && right.to_f64().is_finite();
+
+        match (&left, &right) {
+            (Literal::Complex(_), _) | (_, Literal::Complex(_)) => {
+                self.evaluate_binary_complex(&left, operator, &right)
+            }
+            _ if is_complex_power => self.evaluate_binary_complex(&left, operator, &right),
+            (Literal::Decimal(_), _) | (_, Literal::Decimal(_)) => {
+                self.evaluate_binary_decimal(&left, operator, &right, environment.precision())
+            }
+            (Literal::BigInteger(_) | Literal::Rational(_), Literal::Float(_)) => {
+                self.evaluate_binary_integers(&Literal::Float(left.to_f64()), operator, &right)
+            }
+            (Literal::Float(_), Literal::BigInteger(_) | Literal::Rational(_)) => {
+                self.evaluate_binary_integers(&left, operator, &Literal::Float(right.to_f64()))
+            }
+            (Literal::Rational(_), _) | (_, Literal::Rational(_)) => {
+                self.evaluate_binary_rational(&left, operator, &right, precision)
+            }
+            (
+                Literal::Integer(_) | Literal::BigInteger(_),
+                Literal::Integer(_) | Literal::BigInteger(_),
+            ) if is_inexact && environment.is_exact() => {
+                self.evaluate_binary_rational(&left, operator, &right, precision)
+            }
+            (
+                Literal::Integer(_) | Literal::BigInteger(_),
+                Literal::Integer(_) | Literal::BigInteger(_),
+            ) if is_inexact && environment.is_decimal() => {
+                self.evaluate_binary_decimal(&left, operator, &right, environment.precision())
+            }
+            (Literal::BigInteger(_), _) | (_, Literal::BigInteger(_)) => {
+                self.evaluate_binary_big(&left, operator, &right)
+            }
+            _ => self.evaluate_binary_integers(&left, operator, &right),
+        }
+    }
+
+    fn evaluate_binary_complex(
+        &self,
+        left: &Literal,
+        operator: &Token,
+        right: &Literal,
+    ) -> Result<Literal, ScrError> {
+        let (left, right) = (left.to_complex(), right.to_complex());
+        let boolean = |value: bool| Literal::Integer(value as i128);
+
+        Ok(Literal::from_complex(match operator {
+            Token::Plus(..) => left + right,
+            Token::Minus(..) => left - right,
+            Token::Asterisk(..) => left * right,
+            Token::Slash(..) => left / right,
+
+            // Integer powers are repeated multiplication, so `(1+2i) ** 2` stays exact.
+            Token::AsteriskAsterisk(..)
+                if right.im == 0.0
+                    && right.re.fract() == 0.0
+                    && right.re.abs() <= i32::MAX as f64 =>
+            {
+                left.powi(right.re as i32)
+            }
+
+            // Square roots are taken directly, so `(-4) ** 0.5` is exactly `2i` like `sqrt(-4)`.
+            Token::AsteriskAsterisk(..) if right == Complex64::new(0.5, 0.0) => left.sqrt(),
+            Token::AsteriskAsterisk(..) if right == Complex64::new(-0.5, 0.0) => left.sqrt().inv(),
+
+            Token::AsteriskAsterisk(..) if left.is_zero() && right.re > 0.0 => Complex64::zero(),
+            Token::AsteriskAsterisk(..) => left.powc(right),
+
+            Token::AmpersandAmpersand(..) => {
+                return Ok(boolean(!left.is_zero() && !right.is_zero()));
+            }
+            Token::PipePipe(..) => return Ok(boolean(!left.is_zero() || !right.is_zero())),
+            Token::AssignAssign(..) => return Ok(boolean(left == right)),
+            Token::ExclamationAssign(..) => return Ok(boolean(left != right)),
+
+            Token::GreaterThan(..)
+            | Token::LessThan(..)
+            | Token::GreaterThanAssign(..)
+            | Token::LessThanAssign(..) => {
+                return Err(ScrError::Runtime(
+                    "complex numbers cannot be ordered".to_string(),
+                    operator.span(),
+                    Some("compare their magnitudes with `abs(z)` instead".to_string()),
+                ));
+            }
+
+            _ => {
+                return Err(ScrError::Runtime(
+                    format!("cannot perform `{operator}` on complex numbers"),
+                    operator.span(),
+                    None,
+                ));
+            }
+        }))
+    }
+
+    fn evaluate_binary_decimal(
+        &self,
+        left: &Literal,
+        operator: &Token,
+        right: &Literal,
+        precision: usize,
+    ) -> Result<Literal, ScrError> {
+        let (Some(left), Some(right)) = (left.to_decimal(precision), right.to_decimal(precision))
+        else {
+            // Infinities and NaN have no decimal representation.
+            return self.evaluate_binary_integers(
+                &Literal::Float(left.to_f64()),
+                operator,
+                &Literal::Float(right.to_f64()),
+            );
+        };
+
+        let error = |message| ScrError::Runtime(message, operator.span(), None);
+        let boolean = |value: bool| Literal::Integer(value as i128);
+
+        Ok(Literal::Decimal(match operator {
+            Token::Plus(..) => left.add(&right, precision),
+            Token::Minus(..) => left.sub(&right, precision),
+            Token::Asterisk(..) => left.mul(&right, precision),
+            Token::Slash(..) => left.div(&right, precision).map_err(error)?,
+            Token::Modulo(..) => left.rem(&right, precision).map_err(error)?,
+            Token::AsteriskAsterisk(..) => left.pow(&right, precision).map_err(error)?,
+            Token::GreaterThan(..) => return Ok(boolean(left > right)),
+            Token::LessThan(..) => return Ok(boolean(left < right)),
+
+            Token::AmpersandAmpersand(..) => {
+                return Ok(boolean(!left.is_zero() && !right.is_zero()));
+            }
+            Token::PipePipe(..) => return Ok(boolean(!left.is_zero() || !right.is_zero())),
+            Token::AssignAssign(..) => return Ok(boolean(left == right)),
+            Token::ExclamationAssign(..) => return Ok(boolean(left != right)),
+            Token::GreaterThanAssign(..) => return Ok(boolean(left >= right)),
+            Token::LessThanAssign(..) => return Ok(boolean(left <= right)),
+
+            _ => {
+                return self.evaluate_binary_integers(
+                    &Literal::Float(left.to_f64()),
+                    operator,
+                    &Literal::Float(right.to_f64()),
+                );
+            }
+        }))
+    }
+
+    fn evaluate_binary_rational(
+        &self,
+        left: &Literal,
+        operator: &Token,
+        right: &Literal,
+        precision: Option<usize>,
+    ) -> Result<Literal, ScrError> {
+        let (Some(left), Some(right)) = (left.to_ratio(), right.to_ratio()) else {
+            unreachable!()
+        };
+
+        let overflow = || {
+            ScrError::Runtime(
+                format!("integer overflow in `{operator}`"),
+                operator.span(),
+                Some(format!("integers are limited to {MAX_INTEGER_BITS} bits")),
+            )
+        };
+
+        let boolean = |value: bool| Literal::Integer(value as i128);
+
+        let result = match operator {
+            Token::Plus(..) => left + right,
+            Token::Minus(..) => left - right,
+            Token::Asterisk(..) => left * right,
+
+            Token::Slash(..) | Token::Modulo(..) if right.is_zero() => {
+                return Err(ScrError::Runtime(
+                    "division by zero".to_string(),
+                    operator.span(),
+                    None,
+                ));
+            }
+
+            Token::Slash(..) => left / right,
+
+            Token::Modulo(..) => {
+                let remainder = left % &right;
+
+                if !remainder.is_zero() && remainder.is_negative() != right.is_negative() {
+                    remainder + right
+                } else {
+                    remainder
+                }
+            }
+
+            Token::AsteriskAsterisk(..) if right.is_integer() => {
+                let bits = left.numer().bits().max(left.denom().bits());
+
+                let exponent = i32::try_from(right.to_integer())
+                    .ok()
+                    .filter(|exponent| {
+                        (bits - 1) * u64::from(exponent.unsigned_abs()) < MAX_INTEGER_BITS
+                    })
+                    .ok_or_else(overflow)?;
+
+                if left.is_zero() && exponent < 0 {
+                    return Err(ScrError::Runtime(
+                        "division by zero".to_string(),
+                        operator.span(),
+                        None,
+                    ));
+                }
+
+                left.pow(exponent)
+            }
+
+            Token::GreaterThan(..) => return Ok(boolean(left > right)),
+            Token::LessThan(..) => return Ok(boolean(left < right)),
+
+            Token::AmpersandAmpersand(..) => {
+                return Ok(boolean(!left.is_zero() && !right.is_zero()));
+            }
+            Token::PipePipe(..) => return Ok(boolean(!left.is_zero() || !right.is_zero())),
+            Token::AssignAssign(..) => return Ok(boolean(left == right)),
+            Token::ExclamationAssign(..) => return Ok(boolean(left != right)),
+            Token::GreaterThanAssign(..) => return Ok(boolean(left >= right)),
+            Token::LessThanAssign(..) => return Ok(boolean(left <= right)),
+
+            // Everything without an exact result, like fractional powers, falls back to decimals
+            // in decimal mode and floats otherwise.
+            _ => {
+                let (left, right) = (Literal::Rational(left), Literal::Rational(right));
+
+                return match precision {
+                    Some(precision) => {
+                        self.evaluate_binary_decimal(&left, operator, &right, precision)
+                    }
+                    None => self.evaluate_binary_integers(
+                        &Literal::Float(left.to_f64()),
+                        operator,
+                        &Literal::Float(right.to_f64()),
+                    ),
+                };
+            }
+        };
+
+        if result.numer().bits().max(result.denom().bits()) > MAX_INTEGER_BITS {
+            return Err(overflow());
+        }
+
+        Ok(Literal::from_ratio(result))
+    }
+
+    fn evaluate_binary_big(
+        &self,
+        left: &Literal,
+        operator: &Token,
+        right: &Literal,
+    ) -> Result<Literal, ScrError> {
+        let (Some(left), Some(right)) = (left.to_big(), right.to_big()) else {
+            unreachable!()
+        };
+
+        let overflow = || {
+            ScrError::Runtime(
+                format!("integer overflow in `{operator}`"),
+                operator.span(),
+                Some(format!("integers are limited to {MAX_INTEGER_BITS} bits")),
+            )
+        };
+
+        let boolean = |value: bool| Literal::Integer(value as i128);
+
+        let result = match operator {
+            Token::Plus(..) => left + right,
+            Token::Minus(..) => left - right,
+            Token::Asterisk(..) => left * right,
+
+            Token::Slash(..) => {
+                // Both sides are scaled down together so that quotients of integers beyond the
+                // range of f64 stay finite.
+                let scale = left
+                    .bits()
+                    .max(right.bits())
+                    .saturating_sub(f64::MAX_EXP as u64);
+
+                return Ok(Literal::Float(
+                    Literal::from_big(left >> scale).to_f64()
+                        / Literal::from_big(right >> scale).to_f64(),
+                ));
+            }
+
+            Token::Modulo(..) if right.is_zero() => {
+                return Err(ScrError::Runtime(
+                    "division by zero".to_string(),
+                    operator.span(),
+                    None,
+                ));
+            }
+
+            Token::Modulo(..) => left.mod_floor(&right),
+            Token::Pipe(..) => left | right,
+            Token::Ampersand(..) => left & right,
+            Token::Caret(..) | Token::Xor(..) => left ^ right,
+            Token::GreaterThan(..) => return Ok(boolean(left > right)),
+            Token::LessThan(..) => return Ok(boolean(left < right)),
+
+            Token::AsteriskAsterisk(..) if right.is_negative() => {
+                return Ok(Literal::Float(
+                    Literal::from_big(left)
+                        .to_f64()
+                        .powf(Literal::from_big(right).to_f64()),
+                ));
+            }
+
+            // Only the parity of the exponent matters for 0, 1 and -1, however large it is.
+            Token::AsteriskAsterisk(..) if left.bits() <= 1 => {
+                if right.is_zero() {
+                    BigInt::from(1)
+                } else if right.is_even() {
+                    left.pow(2)
+                } else {
+                    left
+                }
+            }
+
+            Token::AsteriskAsterisk(..) => {
+                let exponent = u32::try_from(&right)
+                    .ok()
+                    .filter(|exponent| (left.bits() - 1) * u64::from(*exponent) < MAX_INTEGER_BITS)
+                    .ok_or_else(overflow)?;
+
+                left.pow(exponent)
+            }
+
+            Token::AmpersandAmpersand(..) => {
+                return Ok(boolean(!left.is_zero() && !right.is_zero()));
+            }
+            Token::PipePipe(..) => return Ok(boolean(!left.is_zero() || !right.is_zero())),
+            Token::AssignAssign(..) => return Ok(boolean(left == right)),
+            Token::GreaterThanGreaterThan(..) => left >> shift_amount(&right, operator)?,
+
+            Token::LessThanLessThan(..) => {
+                let amount = shift_amount(&right, operator)?;
+
+                if left.bits() + amount as u64 > MAX_INTEGER_BITS {
+                    return Err(overflow());
+                }
+
+                left << amount
+            }
+
+            Token::ExclamationAssign(..) => return Ok(boolean(left != right)),
+            Token::GreaterThanAssign(..) => return Ok(boolean(left >= right)),
+            Token::LessThanAssign(..) => return Ok(boolean(left <= right)),
+
+            _ => {
+                return Err(ScrError::Runtime(
+                    format!("unsupported binary operator `{operator}`"),
+                    operator.span(),
+                    None,
+                ));
+            }
+        };
+
+        if result.bits() > MAX_INTEGER_BITS {
+            return Err(overflow());
+        }
+
+        Ok(Literal::from_big(result))
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            Self::Binary(_, operator, _) => precedence::infix(operator)
+                .map(|(precedence, _)| precedence)
+                .unwrap_or(0),
+            Self::Unary(..) => PREFIX_PRECEDENCE,
+            Self::Call(..) | Self::Literal(..) => u8::MAX,
+        }
+    }
+}
+
+impl Display for Expression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let grouped = |expression: &Expression, parenthesize: bool| {
+            if parenthesize {
+                format!("({expression})")
+            } else {
+                expression.to_string()
+            }
+        };
+
+        match self {
+            Self::Unary(operator, operand) => write!(
+                f,
+                "{operator}{}",
+                grouped(operand, operand.precedence() < PREFIX_PRECEDENCE)
+            ),
+
+            Self::Binary(left, operator, right) => {
+                let (precedence, associativity) = precedence::infix(operator).unwrap();
+
+                let (left_parenthesized, right_parenthesized) = match associativity {
+                    Associativity::Left => (
+                        left.precedence() < precedence,
+                        right.precedence() <= precedence,
+                    ),
+                    Associativity::Right => (
+                        left.precedence() <= precedence,
+                        right.precedence() < precedence,
+                    ),
+                };
+
+                write!(
+                    f,
+                    "{} {operator} {}",
+                    grouped(left, left_parenthesized),
+                    grouped(right, right_parenthesized)
+                )
+            }
+
+            Self::Call(name, arguments, ..) => write!(
+                f,
+                "{name}({})",
+                arguments
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+
+            Self::Literal(literal, ..) => write!(f, "{literal}"),
+        }
+    }
+}
+
+// Large enough for factorials and cryptographic sizes while keeping every result quick to print.
+const MAX_INTEGER_BITS: u64 = 1 << 20;
+
+fn check_bounds<'a>(
+    scope: &Scope,
+    operands: impl IntoIterator<Item = &'a Literal>,
+    span: Range<usize>,
+) -> Result<(), ScrError> {
+    if scope.is_expired() {
+        return Err(ScrError::Runtime(
+            "evaluation timed out".to_string(),
+            span,
+            None,
+        ));
+    }
+
+    if operands.into_iter().any(|operand| scope.exceeds(operand)) {
+        return Err(ScrError::Runtime(
+            "operands are too large to evaluate here".to_string(),
+            span,
+            None,
+        ));
+    }
+
+    Ok(())
+}
+
+fn evaluate_complex(
+    function: ComplexFunction,
+    angular: &Angular,
+    arguments: &[Literal],
+    angle_unit: AngleUnit,
+) -> Complex64 {
+    let arguments = arguments
+        .iter()
+        .enumerate()
+        .map(|(index, argument)| match angular {
+            Angular::Argument if index + 1 == arguments.len() => {
+                argument.to_complex().scale(angle_unit.to_radians(1.0))
+            }
+            _ => argument.to_complex(),
+        })
+        .collect::<Vec<_>>();
+
+    let result = function(&arguments);
+
+    match angular {
+        Angular::Result => result.scale(angle_unit.to_unit(1.0)),
+        _ => result,
+    }
+}
+
+fn floored_remainder(left: f64, right: f64) -> f64 {
+    let remainder = left % right;
+
+    if remainder != 0.0 && (remainder < 0.0) != (right < 0.0) {
+        remainder + right
+    } else {
+        remainder
+    }
+}
+
+fn shift_amount<T: Copy + Display>(amount: T, operator: &Token) -> Result<u32, ScrError>
+where
+    u32: TryFrom<T>,
+{
+    u32::try_from(amount).map_err(|_| {
+        ScrError::Runtime(
+            format!("shift amount {amount} is out of range"),
+            operator.span(),
+            None,
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{compiler::parser::syntax::Syntax, engine::Engine};
+
+    fn evaluate(source: &str) -> String {
+        Engine::new().eval(source).unwrap().to_string()
+    }
+
+    fn literal(source: &str, is_exact: bool) -> Literal {
+        let mut engine = Engine::new();
+        engine.environment_mut().set_exact(is_exact);
+
+        let Syntax::Expression(expression) = engine.parse(source).unwrap() else {
+            panic!("`{source}` is not an expression");
+        };
+
+        expression.evaluate(engine.environment()).unwrap()
+    }
+
+    #[test]
+    fn integers_are_signed() {
+        assert_eq!(evaluate("3 - 5"), "-2");
+        assert_eq!(evaluate("-3 < 2"), "1");
+        assert_eq!(evaluate("-3 >= -2"), "0");
+        assert_eq!(evaluate("-8 >> 1"), "-4");
+        assert_eq!(evaluate("-1 >> 10"), "-1");
+    }
+
+    #[test]
+    fn unary_operators() {
+        assert_eq!(evaluate("-2 ** 2"), "-4");
+        assert_eq!(evaluate("+-3"), "-3");
+        assert_eq!(evaluate("- -3"), "3");
+        assert_eq!(evaluate("~5"), "-6");
+        assert_eq!(evaluate("~-1"), "0");
+    }
+
+    #[test]
+    fn logic_gives_zero_or_one() {
+        assert_eq!(evaluate("!(1 == 2)"), "1");
+        assert_eq!(evaluate("!5"), "0");
+        assert_eq!(evaluate("!0"), "1");
+        assert_eq!(evaluate("!!7"), "1");
+        assert_eq!(evaluate("!0.0"), "1");
+    }
+
+    #[test]
+    fn caret_is_xor_unless_it_means_power() {
+        let mut engine = Engine::new().with_caret_power(true);
+
+        assert_eq!(evaluate("6 ^ 3"), "5");
+        assert_eq!(engine.eval("2 ^ 10").unwrap().to_string(), "1024");
+        assert_eq!(engine.eval("6 xor 3").unwrap().to_string(), "5");
+    }
+
+    #[test]
+    fn remainders_take_the_sign_of_the_divisor() {
+        assert_eq!(evaluate("7 % 3"), "1");
+        assert_eq!(evaluate("-7 % 3"), "2");
+        assert_eq!(evaluate("7 % -3"), "-2");
+        assert_eq!(evaluate("-7 % -3"), "-1");
+    }
+
+    #[test]
+    fn overflowing_integers_are_promoted() {
+        assert!(matches!(literal("2 ** 126", false), Literal::Integer(_)));
+        assert!(matches!(literal("2 ** 127", false), Literal::BigInteger(_)));
+        assert!(matches!(
+            literal("(2 ** 127) - 1", false),
+            Literal::Integer(i128::MAX)
+        ));
+        assert!(matches!(
+            literal("-(2 ** 127)", false),
+            Literal::Integer(i128::MIN)
+        ));
+        assert!(matches!(
+            literal("-(2 ** 127) - 1", false),
+            Literal::BigInteger(_)
+        ));
+        assert!(matches!(
+            literal("-(-(2 ** 127))", false),
+            Literal::BigInteger(_)
+        ));
+        assert_eq!(
+            evaluate("-(2 ** 127) * -1"),
+            "170141183460469231731687303715884105728"
+        );
+    }
+
+    #[test]
+    fn remainder_of_the_smallest_integer() {
+        assert!(matches!(
+            literal("-(2 ** 127) % -1", false),
+            Literal::Integer(0)
+        ));
+        assert!(matches!(
+            literal("-(2 ** 127) % 3", false),
+            Literal::Integer(1)
+        ));
+        assert!(matches!(
+            literal("-(2 ** 127) % -3", false),
+            Literal::Integer(-2)
+        ));
+    }
+
+    #[test]
+    fn shifts_overflow_into_big_integers() {
+        assert!(matches!(literal("1 << 126", false), Literal::Integer(_)));
+        assert!(matches!(literal("1 << 127", false), Literal::BigInteger(_)));
+        assert!(matches!(
+            literal("-1 << 127", false),
+            Literal::Integer(i128::MIN)
+        ));
+        assert!(matches!(
+            literal("(1 << 200) >> 199", false),
+            Literal::Integer(2)
+        ));
+        assert_eq!(evaluate("1 << 128"), evaluate("2 ** 128"));
+    }
+
+    #[test]
+    fn rationals_are_kept_in_lowest_terms() {
+        let format = Format::default();
+
+        assert_eq!(literal("6 / 4", true).format(&format), "3/2");
+        assert_eq!(literal("6 / -4", true).format(&format), "-3/2");
+        assert_eq!(literal("-6 / -4", true).format(&format), "3/2");
+        assert_eq!(literal("1/3 + 1/6", true).format(&format), "1/2");
+        assert!(matches!(literal("2/4 * 2", true), Literal::Integer(1)));
+        assert!(matches!(literal("1/3 - 1/3", true), Literal::Integer(0)));
+        assert!(matches!(literal("6 / 4", false), Literal::Float(1.5)));
+    }
+
+    #[test]
+    fn mixed_fractions_keep_the_sign_on_the_whole_part() {
+        let format = Format {
+            is_mixed: true,
+            ..Format::default()
+        };
+
+        assert_eq!(literal("7 / 3", true).format(&format), "2 1/3");
+        assert_eq!(literal("-7 / 3", true).format(&format), "-2 1/3");
+        assert_eq!(literal("7 / -3", true).format(&format), "-2 1/3");
+        assert_eq!(literal("-1 / 3", true).format(&format), "-1/3");
+    }
+
+    #[test]
+    fn square_roots_of_negative_numbers_agree() {
+        assert_eq!(evaluate("sqrt(-4)"), "2i");
+        assert_eq!(evaluate("(-4) ** 0.5"), "2i");
+        assert_eq!(evaluate("(-4) ** -0.5"), evaluate("1 / sqrt(-4)"));
+        assert_eq!(evaluate("(3i) ** 0.5"), evaluate("sqrt(3i)"));
+    }
 
-        result
+    #[test]
+    fn a_standalone_i_is_the_imaginary_unit() {
+        assert_eq!(evaluate("1 - i"), "1-1i");
+        assert_eq!(evaluate("i * i"), "-1");
+        assert_eq!(evaluate("(2+3i)*(1-i)"), "5+1i");
+        assert_eq!(evaluate("fn f(i) = i * 2; f(3)"), "6");
+        assert!(Engine::new().eval("let i = 5").is_err());
     }
 }
@@ -1,375 +1,573 @@
 use std::mem::discriminant;
 
 use expression::{Expression, Literal};
+use num_complex::Complex64;
+use precedence::{Associativity, PREFIX_PRECEDENCE};
 use syntax::Syntax;
 
-use super::lexer::token::Token;
+use super::{decimal::Decimal, error::ScrError, lexer::token::Token};
 
 pub mod expression;
+pub mod precedence;
 pub mod syntax;
 
+// Parsing, evaluating and dropping an expression all recurse once per level of nesting, so deeper
+// input is rejected before it can overflow the stack.
+const MAX_NESTING: usize = 500;
+
 pub struct Parser {
     tokens: Vec<Token>,
     index: usize,
+    depth: usize,
+    caret_is_power: bool,
 }
 
 impl Parser {
     pub fn new(tokens: Vec<Token>) -> Self {
-        Self { tokens, index: 0 }
+        Self {
+            tokens,
+            index: 0,
+            depth: 0,
+            caret_is_power: false,
+        }
     }
 
-    pub fn parse(&mut self) -> Syntax {
+    pub fn with_caret_power(mut self, caret_is_power: bool) -> Self {
+        self.caret_is_power = caret_is_power;
+        self
+    }
+
+    pub fn parse(&mut self) -> Result<Syntax, ScrError> {
+        let mut statements = Vec::new();
+
+        loop {
+            statements.push(self.parse_statement()?);
+
+            match self.current() {
+                Some(Token::Semicolon(..)) => {
+                    self.advance();
+                }
+
+                Some(Token::EndOfLine(..)) | None => break,
+
+                Some(other) => {
+                    return Err(ScrError::Syntax(
+                        format!("unexpected {} after statement", other.describe()),
+                        other.span(),
+                        Some("separate statements with `;`".to_string()),
+                    ));
+                }
+            }
+        }
+
+        statements.retain(|statement| !matches!(statement, Syntax::Nop));
+
+        Ok(match statements.len() {
+            0 => Syntax::Nop,
+            1 => statements.pop().unwrap(),
+            _ => Syntax::Block(statements),
+        })
+    }
+
+    fn parse_statement(&mut self) -> Result<Syntax, ScrError> {
         match self.current() {
             Some(Token::Identifier(..)) => self.parse_name(),
             Some(Token::Decimal(..))
             | Some(Token::Float(..))
+            | Some(Token::Imaginary(..))
             | Some(Token::LeftParenthesis(..)) => self.parse_expression(),
-            Some(Token::EndOfLine(..)) | None => Syntax::Nop,
-
-            _ => {
-                println!("expected statement");
-                Syntax::Nop
-            }
+            Some(operator) if precedence::is_prefix(operator) => self.parse_expression(),
+            Some(Token::EndOfLine(..)) | Some(Token::Semicolon(..)) | None => Ok(Syntax::Nop),
+
+            Some(other) => Err(ScrError::Syntax(
+                format!("expected statement, got {}", other.describe()),
+                other.span(),
+                None,
+            )),
         }
     }
 
-    fn parse_name(&mut self) -> Syntax {
+    fn parse_name(&mut self) -> Result<Syntax, ScrError> {
         let Some(Token::Identifier(name, ..)) = self.current() else {
             unreachable!()
         };
 
         match name.as_str() {
-            "exit" => self.advance_with(Syntax::Command(name.clone())),
-            "clear" => self.advance_with(Syntax::Command(name.clone())),
-            "help" => self.advance_with(Syntax::Command(name.clone())),
-            "list" => self.advance_with(Syntax::Command(name.clone())),
-            "let" => self.parse_variable(),
-            _ => self.parse_expression(),
+            "exit" => Ok(self.advance_with(Syntax::Command(name.clone()))),
+            "clear" => Ok(self.advance_with(Syntax::Command(name.clone()))),
+            "help" => Ok(self.advance_with(Syntax::Command(name.clone()))),
+            "list" => Ok(self.advance_with(Syntax::Command(name.clone()))),
+            "history" => self.parse_history(),
+            "set" => self.parse_set(Syntax::Set),
+            "config" => match self.peek() {
+                Some(Token::Identifier(..)) => self.parse_set(Syntax::Config),
+                _ => Ok(self.advance_with(Syntax::Command(name.clone()))),
+            },
+            "let" | "fn" => self.parse_variable(),
+            _ => match self.peek() {
+                Some(Token::Assign(..)) => self.parse_assignment(),
+                Some(operator) if operator.compound_operator().is_some() => self.parse_assignment(),
+                _ => self.parse_expression(),
+            },
         }
     }
 
-    fn parse_variable(&mut self) -> Syntax {
+    fn parse_history(&mut self) -> Result<Syntax, ScrError> {
+        let search = match self.advance() {
+            Some(Token::Identifier(value, ..))
+            | Some(Token::Decimal(value, ..))
+            | Some(Token::Float(value, ..)) => value.clone(),
+            _ => return Ok(Syntax::History(None)),
+        };
+
+        Ok(self.advance_with(Syntax::History(Some(search))))
+    }
+
+    fn parse_set(
+        &mut self,
+        syntax: fn(String, String, usize) -> Syntax,
+    ) -> Result<Syntax, ScrError> {
         self.advance();
 
-        let name = if let Some(Token::Identifier(name, ..)) = self.current() {
-            name.clone()
-        } else {
-            String::new()
+        let (name, index) = match self.current() {
+            Some(Token::Identifier(name, index)) => (name.clone(), *index),
+            Some(other) => {
+                return Err(ScrError::Syntax(
+                    format!("expected setting name, got {}", other.describe()),
+                    other.span(),
+                    None,
+                ));
+            }
+            None => unreachable!(),
         };
 
-        self.advance();
-        self.eat(Token::Assign(0));
-        let value = self.parse_expression_expression();
+        let value = match self.advance() {
+            Some(Token::Identifier(value, ..))
+            | Some(Token::Decimal(value, ..))
+            | Some(Token::Float(value, ..)) => value.clone(),
+            Some(Token::Text(value, ..)) => value.clone(),
+            Some(keyword @ Token::Xor(..)) => keyword.to_string(),
+            Some(other) => {
+                return Err(ScrError::Syntax(
+                    format!("expected setting value, got {}", other.describe()),
+                    other.span(),
+                    None,
+                ));
+            }
+            None => unreachable!(),
+        };
 
-        if let Some(value) = value {
-            Syntax::Variable(name.clone(), value)
-        } else {
-            Syntax::Nop
-        }
+        Ok(self.advance_with(syntax(name, value, index)))
     }
 
-    fn parse_expression(&mut self) -> Syntax {
-        let expression = self.parse_expression_expression();
+    fn parse_variable(&mut self) -> Result<Syntax, ScrError> {
+        let is_function =
+            matches!(self.current(), Some(Token::Identifier(keyword, ..)) if keyword == "fn");
+        self.advance();
 
-        if let Some(expression) = expression {
-            Syntax::Expression(expression)
-        } else {
-            Syntax::Nop
-        }
-    }
+        let (name, index) = match self.current() {
+            Some(Token::Identifier(name, index)) if !name.starts_with('$') => {
+                (name.clone(), *index)
+            }
+            Some(other) => {
+                return Err(ScrError::Syntax(
+                    format!("expected variable name, got {}", other.describe()),
+                    other.span(),
+                    None,
+                ));
+            }
+            None => unreachable!(),
+        };
 
-    fn parse_expression_expression(&mut self) -> Option<Expression> {
-        self.parse_logical_or_expression()
-    }
+        self.advance();
 
-    fn parse_logical_or_expression(&mut self) -> Option<Expression> {
-        let left = self.parse_logical_and_expression();
+        if is_function || matches!(self.current(), Some(Token::LeftParenthesis(..))) {
+            let parameters = self.parse_parameters()?;
+            self.eat(Token::Assign(0))?;
+            let body = self.parse_expression_expression()?;
 
-        if left.is_none() {
-            return left;
+            return Ok(Syntax::Function(name, parameters, body, index));
         }
 
-        let mut left = left.unwrap();
-
-        Some(match self.current() {
-            Some(Token::PipePipe(..)) => {
-                let operator = self.current().unwrap().clone();
-                self.eat(operator.clone());
-                let right = self.parse_expression_expression();
-                left =
-                    Expression::Binary(Box::new(left), operator.clone(), Box::new(right.unwrap()));
-                left
-            }
+        self.eat(Token::Assign(0))?;
+        let value = self.parse_expression_expression()?;
 
-            _ => left,
-        })
+        Ok(Syntax::Variable(name, value, index))
     }
 
-    fn parse_logical_and_expression(&mut self) -> Option<Expression> {
-        let left = self.parse_bitwise_inclusive_or_expression();
+    fn parse_assignment(&mut self) -> Result<Syntax, ScrError> {
+        let Some(Token::Identifier(name, index)) = self.current().cloned() else {
+            unreachable!()
+        };
 
-        if left.is_none() {
-            return left;
-        }
+        let operator = self.advance().unwrap().compound_operator();
+        self.advance();
+        let value = self.parse_expression_expression()?;
+
+        let value = match operator {
+            Some(operator) => Expression::Binary(
+                Box::new(Expression::Literal(Literal::Name(name.clone()), index)),
+                self.resolve(operator),
+                Box::new(value),
+            ),
+            None => value,
+        };
 
-        let mut left = left.unwrap();
+        Ok(Syntax::Assignment(name, value, index))
+    }
+
+    fn parse_parameters(&mut self) -> Result<Vec<String>, ScrError> {
+        self.eat(Token::LeftParenthesis(0))?;
+        let mut parameters = Vec::<String>::new();
 
-        Some(match self.current() {
-            Some(Token::AmpersandAmpersand(..)) => {
-                let operator = self.current().unwrap().clone();
-                self.eat(operator.clone());
-                let right = self.parse_expression_expression();
-                left =
-                    Expression::Binary(Box::new(left), operator.clone(), Box::new(right.unwrap()));
-                left
+        while !matches!(self.current(), Some(Token::RightParenthesis(..))) {
+            if !parameters.is_empty() {
+                self.eat(Token::Comma(0))?;
             }
 
-            _ => left,
-        })
+            match self.current() {
+                Some(Token::Identifier(parameter, ..)) if parameters.contains(parameter) => {
+                    return Err(ScrError::Syntax(
+                        format!("duplicate parameter `{parameter}`"),
+                        self.current().unwrap().span(),
+                        None,
+                    ));
+                }
+
+                Some(Token::Identifier(parameter, ..)) => {
+                    parameters.push(parameter.clone());
+                    self.advance();
+                }
+
+                Some(other) => {
+                    return Err(ScrError::Syntax(
+                        format!("expected parameter name, got {}", other.describe()),
+                        other.span(),
+                        None,
+                    ));
+                }
+
+                None => unreachable!(),
+            }
+        }
+
+        self.eat(Token::RightParenthesis(0))?;
+
+        Ok(parameters)
     }
 
-    fn parse_bitwise_inclusive_or_expression(&mut self) -> Option<Expression> {
-        let left = self.parse_bitwise_exclusive_or_expression();
+    fn parse_expression(&mut self) -> Result<Syntax, ScrError> {
+        Ok(Syntax::Expression(self.parse_expression_expression()?))
+    }
 
-        if left.is_none() {
-            return left;
-        }
+    fn parse_expression_expression(&mut self) -> Result<Expression, ScrError> {
+        self.parse_binary_expression(0)
+    }
 
-        let mut left = left.unwrap();
+    fn parse_binary_expression(&mut self, minimum: u8) -> Result<Expression, ScrError> {
+        let depth = self.depth;
+        self.nest()?;
 
-        Some(match self.current() {
-            Some(Token::Pipe(..)) => {
-                let operator = self.current().unwrap().clone();
-                self.eat(operator.clone());
-                let right = self.parse_expression_expression();
-                left =
-                    Expression::Binary(Box::new(left), operator.clone(), Box::new(right.unwrap()));
-                left
-            }
+        let mut left = self.parse_unary_expression()?;
 
-            _ => left,
-        })
-    }
+        while let Some(operator) = self.current().cloned().map(|token| self.resolve(token))
+            && let Some((precedence, associativity)) = precedence::infix(&operator)
+            && precedence >= minimum
+        {
+            self.advance();
+            // Every operator puts the expression so far one level deeper.
+            self.nest()?;
 
-    fn parse_bitwise_exclusive_or_expression(&mut self) -> Option<Expression> {
-        let left = self.parse_bitwise_and_expression();
+            let right = self.parse_binary_expression(match associativity {
+                Associativity::Left => precedence + 1,
+                Associativity::Right => precedence,
+            })?;
 
-        if left.is_none() {
-            return left;
+            left = Expression::Binary(Box::new(left), operator, Box::new(right));
         }
 
-        let mut left = left.unwrap();
-
-        Some(match self.current() {
-            Some(Token::Ampersand(..)) => {
-                let operator = self.current().unwrap().clone();
-                self.eat(operator.clone());
-                let right = self.parse_expression_expression();
-                left =
-                    Expression::Binary(Box::new(left), operator.clone(), Box::new(right.unwrap()));
-                left
-            }
+        self.depth = depth;
 
-            _ => left,
-        })
+        Ok(left)
     }
 
-    fn parse_bitwise_and_expression(&mut self) -> Option<Expression> {
-        let left = self.parse_rational_equality_expression();
+    fn nest(&mut self) -> Result<(), ScrError> {
+        self.depth += 1;
 
-        if left.is_none() {
-            return left;
+        if self.depth <= MAX_NESTING {
+            return Ok(());
         }
 
-        let mut left = left.unwrap();
+        Err(ScrError::Syntax(
+            "expression is nested too deeply".to_string(),
+            self.current().map(Token::span).unwrap_or_default(),
+            Some(format!("at most {MAX_NESTING} levels are allowed")),
+        ))
+    }
 
-        Some(match self.current() {
-            Some(Token::Ampersand(..)) => {
-                let operator = self.current().unwrap().clone();
-                self.eat(operator.clone());
-                let right = self.parse_expression_expression();
-                left =
-                    Expression::Binary(Box::new(left), operator.clone(), Box::new(right.unwrap()));
-                left
+    fn parse_unary_expression(&mut self) -> Result<Expression, ScrError> {
+        match self.current() {
+            Some(operator) if precedence::is_prefix(operator) => {
+                let operator = operator.clone();
+                self.advance();
+                let operand = self.parse_binary_expression(PREFIX_PRECEDENCE)?;
+                Ok(Expression::Unary(operator, Box::new(operand)))
             }
 
-            _ => left,
-        })
+            _ => self.parse_literal_expression(),
+        }
     }
 
-    fn parse_rational_equality_expression(&mut self) -> Option<Expression> {
-        let left = self.parse_rational_difference_expression();
+    fn parse_literal_expression(&mut self) -> Result<Expression, ScrError> {
+        match self.current() {
+            Some(Token::Identifier(name, index)) => {
+                let (name, index) = (name.clone(), *index);
 
-        if left.is_none() {
-            return left;
-        }
+                match self.advance() {
+                    Some(Token::LeftParenthesis(..)) => self.parse_call_expression(name, index),
+                    _ => Ok(Expression::Literal(Literal::Name(name), index)),
+                }
+            }
 
-        let mut left = left.unwrap();
+            Some(Token::Decimal(value, index)) => {
+                let literal = match value.parse() {
+                    Ok(value) => Literal::Integer(value),
+                    Err(_) => Literal::BigInteger(value.parse().unwrap()),
+                };
 
-        Some(match self.current() {
-            Some(Token::AssignAssign(..)) | Some(Token::ExclamationAssign(..)) => {
-                let operator = self.current().unwrap().clone();
-                self.eat(operator.clone());
-                let right = self.parse_expression_expression();
-                left =
-                    Expression::Binary(Box::new(left), operator.clone(), Box::new(right.unwrap()));
-                left
+                Ok(self.advance_with(Expression::Literal(literal, *index)))
             }
 
-            _ => left,
-        })
-    }
+            // Kept as written, so decimal mode sees `0.1` exactly; it only becomes a float when
+            // evaluated outside of decimal mode.
+            Some(Token::Float(value, index)) => {
+                let literal = Literal::Decimal(Decimal::parse(value));
 
-    fn parse_rational_difference_expression(&mut self) -> Option<Expression> {
-        let left = self.parse_bitwise_shift_expression();
+                Ok(self.advance_with(Expression::Literal(literal, *index)))
+            }
 
-        if left.is_none() {
-            return left;
-        }
+            Some(Token::Imaginary(value, index)) => {
+                let literal = Literal::Complex(Complex64::new(0.0, value.parse().unwrap()));
 
-        let mut left = left.unwrap();
-
-        Some(match self.current() {
-            Some(Token::GreaterThan(..))
-            | Some(Token::LessThan(..))
-            | Some(Token::GreaterThanAssign(..))
-            | Some(Token::LessThanAssign(..)) => {
-                let operator = self.current().unwrap().clone();
-                self.eat(operator.clone());
-                let right = self.parse_expression_expression();
-                left =
-                    Expression::Binary(Box::new(left), operator.clone(), Box::new(right.unwrap()));
-                left
+                Ok(self.advance_with(Expression::Literal(literal, *index)))
             }
 
-            _ => left,
-        })
-    }
+            Some(Token::LeftParenthesis(..)) => {
+                self.advance();
+                let expression = self.parse_expression_expression()?;
+                self.eat(Token::RightParenthesis(0))?;
+                Ok(expression)
+            }
 
-    fn parse_bitwise_shift_expression(&mut self) -> Option<Expression> {
-        let left = self.parse_additive_expression();
+            Some(other) => Err(ScrError::Syntax(
+                format!("expected literal, got {}", other.describe()),
+                other.span(),
+                None,
+            )),
 
-        if left.is_none() {
-            return left;
+            None => unreachable!(),
         }
+    }
+
+    fn parse_call_expression(
+        &mut self,
+        name: String,
+        index: usize,
+    ) -> Result<Expression, ScrError> {
+        self.eat(Token::LeftParenthesis(0))?;
+        let mut arguments = Vec::new();
 
-        let mut left = left.unwrap();
+        if !matches!(self.current(), Some(Token::RightParenthesis(..))) {
+            arguments.push(self.parse_expression_expression()?);
 
-        Some(match self.current() {
-            Some(Token::GreaterThanGreaterThan(..)) | Some(Token::LessThanLessThan(..)) => {
-                let operator = self.current().unwrap().clone();
-                self.eat(operator.clone());
-                let right = self.parse_expression_expression();
-                left =
-                    Expression::Binary(Box::new(left), operator.clone(), Box::new(right.unwrap()));
-                left
+            while let Some(Token::Comma(..)) = self.current() {
+                self.advance();
+                arguments.push(self.parse_expression_expression()?);
             }
+        }
 
-            _ => left,
-        })
-    }
+        self.eat(Token::RightParenthesis(0))?;
 
-    fn parse_additive_expression(&mut self) -> Option<Expression> {
-        let left = self.parse_multiplicative_expression();
+        Ok(Expression::Call(name, arguments, index))
+    }
 
-        if left.is_none() {
-            return left;
+    fn resolve(&self, token: Token) -> Token {
+        match token {
+            Token::Caret(index) if self.caret_is_power => Token::AsteriskAsterisk(index),
+            other => other,
         }
+    }
 
-        let mut left = left.unwrap();
+    fn advance_with<T>(&mut self, any: T) -> T {
+        self.advance();
+        any
+    }
 
-        Some(match self.current() {
-            Some(Token::Plus(..)) | Some(Token::Minus(..)) => {
-                let operator = self.current().unwrap().clone();
-                self.eat(operator.clone());
-                let right = self.parse_expression_expression();
-                left =
-                    Expression::Binary(Box::new(left), operator.clone(), Box::new(right.unwrap()));
-                left
+    fn eat(&mut self, expect: Token) -> Result<Option<&Token>, ScrError> {
+        match self.current() {
+            Some(current) if discriminant(current) != discriminant(&expect) => {
+                Err(ScrError::Syntax(
+                    format!("expected {}, got {}", expect.describe(), current.describe()),
+                    current.span(),
+                    None,
+                ))
             }
 
-            _ => left,
-        })
+            Some(_) => Ok(self.advance()),
+            None => unreachable!(),
+        }
+    }
+
+    fn advance(&mut self) -> Option<&Token> {
+        self.index += 1;
+        self.current()
     }
 
-    fn parse_multiplicative_expression(&mut self) -> Option<Expression> {
-        let left = self.parse_literal_expression();
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.index + 1)
+    }
 
-        if left.is_none() {
-            return left;
-        }
+    fn current(&self) -> Option<&Token> {
+        self.tokens.get(self.index)
+    }
+}
 
-        let mut left = left.unwrap();
+#[cfg(test)]
+mod tests {
+    use std::thread;
 
-        Some(match self.current() {
-            Some(Token::Asterisk(..)) | Some(Token::Slash(..)) | Some(Token::Modulo(..)) => {
-                let operator = self.current().unwrap().clone();
-                self.eat(operator.clone());
-                let right = self.parse_expression_expression();
-                left =
-                    Expression::Binary(Box::new(left), operator.clone(), Box::new(right.unwrap()));
-                left
-            }
+    use super::*;
+    use crate::{compiler::lexer::Lexer, engine::Engine};
 
-            _ => left,
-        })
+    fn parse(source: &str) -> String {
+        parse_with(source, false)
     }
 
-    fn parse_literal_expression(&mut self) -> Option<Expression> {
-        match self.current() {
-            Some(Token::Identifier(name, index)) => self.advance_with(Some(Expression::Literal(
-                Literal::Name(name.clone()),
-                index.clone(),
-            ))),
+    fn parse_with(source: &str, caret_is_power: bool) -> String {
+        let tokens = Lexer::new(source.to_string()).tokenize().unwrap();
+        let syntax = Parser::new(tokens)
+            .with_caret_power(caret_is_power)
+            .parse()
+            .unwrap();
 
-            Some(Token::Decimal(value, index)) => self.advance_with(Some(Expression::Literal(
-                Literal::Integer(value.parse().unwrap()),
-                index.clone(),
-            ))),
+        let Syntax::Expression(expression) = syntax else {
+            panic!("`{source}` is not an expression");
+        };
 
-            Some(Token::Float(value, index)) => self.advance_with(Some(Expression::Literal(
-                Literal::Float(value.parse().unwrap()),
-                index.clone(),
-            ))),
+        grouped(&expression)
+    }
 
-            Some(Token::LeftParenthesis(..)) => {
-                self.advance();
-                let expression = self.parse_expression_expression();
-                self.eat(Token::RightParenthesis(0));
-                expression
-            }
+    fn parse_syntax(source: String) -> Result<Syntax, ScrError> {
+        Parser::new(Lexer::new(source).tokenize().unwrap()).parse()
+    }
 
-            _ => {
-                println!("expected literal");
-                None
+    // Every operation in parentheses, so the shape of the tree is visible.
+    fn grouped(expression: &Expression) -> String {
+        match expression {
+            Expression::Unary(operator, operand) => format!("({operator}{})", grouped(operand)),
+            Expression::Binary(left, operator, right) => {
+                format!("({} {operator} {})", grouped(left), grouped(right))
             }
+            other => other.to_string(),
         }
     }
 
-    fn advance_with<T>(&mut self, any: T) -> T {
-        self.advance();
-        any
+    #[test]
+    fn left_associative_operators_group_left() {
+        assert_eq!(parse("10 - 2 - 3"), "((10 - 2) - 3)");
+        assert_eq!(parse("8 / 4 / 2"), "((8 / 4) / 2)");
     }
 
-    fn eat(&mut self, expect: Token) -> Option<&Token> {
-        if let Some(current) = self.current() {
-            if discriminant(current) != discriminant(&expect) {
-                println!("expected: {expect:?}, got: {current:?}");
-                return None;
-            } else {
-                return self.advance();
-            }
-        }
+    #[test]
+    fn multiplication_binds_tighter_than_addition() {
+        assert_eq!(parse("2 * 3 + 4"), "((2 * 3) + 4)");
+        assert_eq!(parse("4 + 2 * 3"), "(4 + (2 * 3))");
+    }
 
-        None
+    #[test]
+    fn power_is_right_associative() {
+        assert_eq!(parse("2 ** 3 ** 2"), "(2 ** (3 ** 2))");
     }
 
-    fn advance(&mut self) -> Option<&Token> {
-        self.index += 1;
-        self.current()
+    #[test]
+    fn unary_operators_bind_below_power() {
+        assert_eq!(parse("-2 ** 2"), "(-(2 ** 2))");
+        assert_eq!(parse("2 ** -1"), "(2 ** (-1))");
     }
 
-    fn current(&self) -> Option<&Token> {
-        self.tokens.get(self.index)
+    #[test]
+    fn caret_is_xor_unless_it_means_power() {
+        assert_eq!(parse("1 | 6 ^ 3 & 5"), "(1 | (6 ^ (3 & 5)))");
+        assert_eq!(parse_with("2 ^ 3 ^ 2", true), "(2 ** (3 ** 2))");
+        assert_eq!(parse_with("1 | 6 xor 3 & 5", true), "(1 | (6 xor (3 & 5)))");
+    }
+
+    #[test]
+    fn semicolons_separate_statements() {
+        let Ok(Syntax::Block(statements)) =
+            parse_syntax("let a = 3; let b = 4; sqrt(a**2+b**2)".to_string())
+        else {
+            panic!("expected a block");
+        };
+
+        assert!(matches!(
+            statements.as_slice(),
+            [
+                Syntax::Variable(..),
+                Syntax::Variable(..),
+                Syntax::Expression(..)
+            ]
+        ));
+        assert!(matches!(
+            parse_syntax("1;;2;".to_string()),
+            Ok(Syntax::Block(statements)) if statements.len() == 2
+        ));
+        assert!(matches!(
+            parse_syntax("1;".to_string()),
+            Ok(Syntax::Expression(..))
+        ));
+    }
+
+    #[test]
+    fn tokens_after_a_statement_are_an_error() {
+        for source in ["1 2", "let a = 1 b", "f(1) )", "history sin x"] {
+            let error = parse_syntax(source.to_string()).err().unwrap();
+
+            assert!(matches!(error, ScrError::Syntax(..)));
+            assert!(error.to_string().ends_with("after statement"), "{error}");
+        }
+    }
+
+    #[test]
+    fn deep_nesting_is_a_syntax_error() {
+        let depth = MAX_NESTING;
+
+        let results = thread::Builder::new()
+            .stack_size(Engine::stack_size(0))
+            .spawn(move || {
+                [
+                    format!("{}1{}", "(".repeat(depth - 1), ")".repeat(depth - 1)),
+                    format!("{}1{}", "(".repeat(depth), ")".repeat(depth)),
+                    format!("{}1", "-".repeat(depth)),
+                    vec!["1"; depth + 1].join(" + "),
+                    "(".repeat(1_000_000),
+                ]
+                .map(|source| {
+                    parse_syntax(source)
+                        .map(|_| ())
+                        .map_err(|error| error.to_string())
+                })
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+
+        let [at_limit, beyond @ ..] = results;
+
+        assert!(at_limit.is_ok());
+
+        for result in beyond {
+            assert!(result.unwrap_err().contains("nested too deeply"));
+        }
     }
 }
@@ -0,0 +1,35 @@
+use crate::compiler::lexer::token::Token;
+
+pub enum Associativity {
+    Left,
+    Right,
+}
+
+pub const PREFIX_PRECEDENCE: u8 = 11;
+
+pub fn infix(token: &Token) -> Option<(u8, Associativity)> {
+    Some(match token {
+        Token::PipePipe(..) => (1, Associativity::Left),
+        Token::AmpersandAmpersand(..) => (2, Associativity::Left),
+        Token::Pipe(..) => (3, Associativity::Left),
+        Token::Caret(..) | Token::Xor(..) => (4, Associativity::Left),
+        Token::Ampersand(..) => (5, Associativity::Left),
+        Token::AssignAssign(..) | Token::ExclamationAssign(..) => (6, Associativity::Left),
+        Token::GreaterThan(..)
+        | Token::LessThan(..)
+        | Token::GreaterThanAssign(..)
+        | Token::LessThanAssign(..) => (7, Associativity::Left),
+        Token::GreaterThanGreaterThan(..) | Token::LessThanLessThan(..) => (8, Associativity::Left),
+        Token::Plus(..) | Token::Minus(..) => (9, Associativity::Left),
+        Token::Asterisk(..) | Token::Slash(..) | Token::Modulo(..) => (10, Associativity::Left),
+        Token::AsteriskAsterisk(..) => (12, Associativity::Right),
+        _ => return None,
+    })
+}
+
+pub fn is_prefix(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::Plus(..) | Token::Minus(..) | Token::Exclamation(..) | Token::Tilde(..)
+    )
+}
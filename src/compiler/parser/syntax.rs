@@ -1,8 +1,14 @@
 use super::expression::Expression;
 
 pub enum Syntax {
+    Block(Vec<Syntax>),
     Command(String),
+    History(Option<String>),
+    Set(String, String, usize),
+    Config(String, String, usize),
     Expression(Expression),
-    Variable(String, Expression),
+    Variable(String, Expression, usize),
+    Assignment(String, Expression, usize),
+    Function(String, Vec<String>, Expression, usize),
     Nop,
 }
@@ -0,0 +1,477 @@
+use std::{fmt::Display, sync::Arc};
+
+use num_bigint::BigInt;
+use num_complex::Complex64;
+use num_rational::BigRational;
+
+use crate::compiler::{
+    builtins,
+    decimal::Decimal,
+    environment::{AngleUnit, Environment, Function, NativeFunction},
+    error::{ScrError, suggest},
+    format::Format,
+    lexer::Lexer,
+    parser::{Parser, expression::Literal, syntax::Syntax},
+};
+
+// Expressions nested as deeply as the parser allows fit in the base. User-defined functions
+// recurse on the native stack on top of it, about 24 KiB per call in unoptimized builds.
+const STACK_BASE: usize = 8 * 1024 * 1024;
+const STACK_PER_CALL: usize = 64 * 1024;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Integer(BigInt),
+    Rational(BigRational),
+    Decimal(Decimal),
+    Float(f64),
+    Complex(Complex64),
+    // What statements like `let x = 2` or `fn f(x) = x * 2` give.
+    Unit,
+}
+
+impl Value {
+    pub fn to_f64(&self) -> f64 {
+        literal_of(self.clone()).map_or(f64::NAN, |value| value.to_f64())
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match literal_of(self.clone()) {
+            Some(value) => write!(f, "{}", value.format(&Format::default())),
+            None => write!(f, "()"),
+        }
+    }
+}
+
+impl From<i64> for Value {
+    fn from(value: i64) -> Self {
+        Self::Integer(BigInt::from(value))
+    }
+}
+
+impl From<BigInt> for Value {
+    fn from(value: BigInt) -> Self {
+        Self::Integer(value)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Self::Float(value)
+    }
+}
+
+/// Evaluates on the caller's thread, so that thread needs `Engine::stack_size(depth)` bytes of
+/// stack for the recursion depth allowed by `set_max_depth` (256 by default).
+pub struct Engine {
+    environment: Environment,
+    caret_is_power: bool,
+}
+
+impl Engine {
+    pub fn new() -> Self {
+        Self {
+            environment: Environment::new(),
+            caret_is_power: false,
+        }
+    }
+
+    /// The stack evaluation needs when functions may recurse `max_depth` calls deep.
+    pub fn stack_size(max_depth: usize) -> usize {
+        STACK_BASE + STACK_PER_CALL * (max_depth + 1)
+    }
+
+    pub fn with_caret_power(mut self, caret_is_power: bool) -> Self {
+        self.caret_is_power = caret_is_power;
+        self
+    }
+
+    pub fn set_caret_power(&mut self, caret_is_power: bool) {
+        self.caret_is_power = caret_is_power;
+    }
+
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.environment.set_max_depth(max_depth);
+    }
+
+    pub fn set_angle_unit(&mut self, angle_unit: AngleUnit) {
+        self.environment.set_angle_unit(angle_unit);
+    }
+
+    pub fn set_exact(&mut self, is_exact: bool) {
+        self.environment.set_exact(is_exact);
+    }
+
+    pub fn set_decimal(&mut self, is_decimal: bool, precision: usize) {
+        self.environment.set_decimal(is_decimal, precision);
+    }
+
+    #[doc(hidden)]
+    pub fn environment(&self) -> &Environment {
+        &self.environment
+    }
+
+    #[doc(hidden)]
+    pub fn environment_mut(&mut self) -> &mut Environment {
+        &mut self.environment
+    }
+
+    pub fn eval(&mut self, source: &str) -> Result<Value, ScrError> {
+        let syntax = self.parse(source)?;
+
+        self.execute(syntax)
+    }
+
+    #[doc(hidden)]
+    pub fn parse(&self, source: &str) -> Result<Syntax, ScrError> {
+        let tokens = Lexer::new(source.to_string()).tokenize()?;
+
+        Parser::new(tokens)
+            .with_caret_power(self.caret_is_power)
+            .parse()
+    }
+
+    // The value of an input ending in an expression becomes the next numbered result, `$n`, `ans`
+    // and `_`.
+    #[doc(hidden)]
+    pub fn execute(&mut self, syntax: Syntax) -> Result<Value, ScrError> {
+        Ok(match self.execute_statement(syntax)? {
+            Some(value) => {
+                self.environment.record(value.clone());
+                value_of(value)
+            }
+
+            None => Value::Unit,
+        })
+    }
+
+    fn execute_statement(&mut self, syntax: Syntax) -> Result<Option<Literal>, ScrError> {
+        match syntax {
+            Syntax::Block(statements) => {
+                let mut value = None;
+
+                for statement in statements {
+                    value = self.execute_statement(statement)?;
+                }
+
+                Ok(value)
+            }
+
+            Syntax::Command(name) => Err(ScrError::Runtime(
+                format!("`{name}` is only available in the REPL"),
+                0..name.len(),
+                None,
+            )),
+
+            Syntax::History(..) => Err(ScrError::Runtime(
+                "`history` is only available in the REPL".to_string(),
+                0.."history".len(),
+                None,
+            )),
+
+            Syntax::Set(name, _, index) | Syntax::Config(name, _, index) => Err(ScrError::Runtime(
+                "settings are only available in the REPL".to_string(),
+                index..index + name.len(),
+                None,
+            )),
+
+            Syntax::Expression(expression) => expression.evaluate(&self.environment).map(Some),
+
+            Syntax::Function(name, _, _, index)
+                if name == "if" || builtins::lookup(&name).is_some() =>
+            {
+                Err(ScrError::Runtime(
+                    format!("cannot redefine built-in function `{name}`"),
+                    index..index + name.len(),
+                    None,
+                ))
+            }
+
+            Syntax::Function(name, parameters, body, _) => {
+                self.environment
+                    .define_function(name, Function { parameters, body });
+
+                Ok(None)
+            }
+
+            Syntax::Variable(name, _, index) | Syntax::Assignment(name, _, index)
+                if self.environment.is_constant(&name) =>
+            {
+                Err(ScrError::Runtime(
+                    format!("cannot assign to constant `{name}`"),
+                    index..index + name.len(),
+                    None,
+                ))
+            }
+
+            Syntax::Assignment(name, _, index) if !self.environment.is_variable(&name) => {
+                Err(ScrError::Runtime(
+                    format!("cannot assign to undefined variable `{name}`"),
+                    index..index + name.len(),
+                    suggest(
+                        &name,
+                        self.environment.variables().map(|(name, _)| name.as_str()),
+                    )
+                    .or_else(|| Some(format!("use `let {name} = ...` to define it"))),
+                ))
+            }
+
+            Syntax::Variable(name, value, _) | Syntax::Assignment(name, value, _) => {
+                let value = value.evaluate(&self.environment)?;
+                self.environment.define(name, value);
+
+                Ok(None)
+            }
+
+            Syntax::Nop => Ok(None),
+        }
+    }
+
+    pub fn set_var(&mut self, name: &str, value: Value) -> Result<(), ScrError> {
+        if self.environment.is_constant(name) {
+            return Err(ScrError::Runtime(
+                format!("cannot assign to constant `{name}`"),
+                0..name.len(),
+                None,
+            ));
+        }
+
+        let value = literal_of(value).ok_or_else(|| {
+            ScrError::Runtime(
+                format!("cannot assign `()` to `{name}`"),
+                0..name.len(),
+                None,
+            )
+        })?;
+
+        self.environment.define(name.to_string(), value);
+
+        Ok(())
+    }
+
+    pub fn get_var(&self, name: &str) -> Option<Value> {
+        self.environment.get(name).cloned().map(value_of)
+    }
+
+    pub fn register_function(
+        &mut self,
+        name: &str,
+        parameters: &[&str],
+        function: impl Fn(&[Value]) -> Result<Value, String> + Send + Sync + 'static,
+    ) -> Result<(), ScrError> {
+        if name == "if" || builtins::lookup(name).is_some() {
+            return Err(ScrError::Runtime(
+                format!("cannot redefine built-in function `{name}`"),
+                0..name.len(),
+                None,
+            ));
+        }
+
+        self.environment.define_native_function(
+            name.to_string(),
+            NativeFunction {
+                parameters: parameters
+                    .iter()
+                    .map(|parameter| parameter.to_string())
+                    .collect(),
+                function: Arc::new(move |arguments| {
+                    let arguments = arguments.iter().cloned().map(value_of).collect::<Vec<_>>();
+
+                    function(&arguments).and_then(|value| {
+                        literal_of(value)
+                            .ok_or_else(|| "returned `()` instead of a number".to_string())
+                    })
+                }),
+            },
+        );
+
+        Ok(())
+    }
+}
+
+fn literal_of(value: Value) -> Option<Literal> {
+    Some(match value {
+        Value::Integer(value) => Literal::from_big(value),
+        Value::Rational(value) => Literal::from_ratio(value),
+        Value::Decimal(value) => Literal::Decimal(value),
+        Value::Float(value) => Literal::Float(value),
+        Value::Complex(value) => Literal::from_complex(value),
+        Value::Unit => return None,
+    })
+}
+
+// Evaluation resolves every name, so no value is ever a `Literal::Name`.
+fn value_of(literal: Literal) -> Value {
+    match literal {
+        Literal::Integer(value) => Value::Integer(BigInt::from(value)),
+        Literal::BigInteger(value) => Value::Integer(value),
+        Literal::Rational(value) => Value::Rational(value),
+        Literal::Decimal(value) => Value::Decimal(value),
+        Literal::Float(value) => Value::Float(value),
+        Literal::Complex(value) => Value::Complex(value),
+        Literal::Name(..) => unreachable!(),
+    }
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn statements_have_no_value() {
+        let mut engine = Engine::new();
+
+        assert_eq!(engine.eval("fn f(x) = x * 2").unwrap(), Value::Unit);
+        assert_eq!(engine.eval("let y = 3").unwrap(), Value::Unit);
+        assert_eq!(engine.eval("f(y)").unwrap(), Value::from(6));
+    }
+
+    #[test]
+    fn expression_results_are_numbered() {
+        let mut engine = Engine::new();
+
+        engine.eval("2 * 3").unwrap();
+        engine.eval("let x = 4; x").unwrap();
+        engine.eval("x + 1; let y = 0").unwrap();
+
+        assert_eq!(engine.eval("$1 + $2").unwrap(), Value::from(10));
+        assert_eq!(engine.eval("ans * _").unwrap(), Value::from(100));
+        assert!(engine.eval("$5").is_err());
+    }
+
+    #[test]
+    fn functions_are_defined_with_fn_or_let() {
+        let mut engine = Engine::new();
+
+        engine.eval("let f(x, y) = x ** 2 + y").unwrap();
+        engine
+            .eval("fn fact(n) = if(n <= 1, 1, n * fact(n - 1))")
+            .unwrap();
+
+        assert_eq!(engine.eval("f(3, 1)").unwrap(), Value::from(10));
+        assert_eq!(engine.eval("fact(10)").unwrap(), Value::from(3_628_800));
+        assert_eq!(
+            engine.eval("f(1)").unwrap_err().to_string(),
+            "runtime error: `f` takes 2 argument(s) but 1 were given"
+        );
+        assert_eq!(
+            engine.eval("fn sin(x) = x").unwrap_err().to_string(),
+            "runtime error: cannot redefine built-in function `sin`"
+        );
+    }
+
+    #[test]
+    fn recursion_stops_at_the_depth_limit() {
+        let mut engine = Engine::new();
+        engine.set_max_depth(10);
+
+        engine
+            .eval("fn g(n) = if(n <= 0, 0, 1 + g(n - 1))")
+            .unwrap();
+
+        assert_eq!(engine.eval("g(9)").unwrap(), Value::from(9));
+        assert_eq!(
+            engine.eval("g(10)").unwrap_err().to_string(),
+            "runtime error: in `g`: maximum recursion depth of 10 exceeded"
+        );
+    }
+
+    #[test]
+    fn compound_assignment_updates_defined_variables() {
+        let mut engine = Engine::new();
+
+        engine.eval("let x = 2; x += 3; x **= 2").unwrap();
+        assert_eq!(engine.get_var("x"), Some(Value::from(25)));
+
+        engine.eval("x = 6; x ^= 3; x <<= 2; x %= 7").unwrap();
+        assert_eq!(engine.get_var("x"), Some(Value::from(6)));
+    }
+
+    #[test]
+    fn assignment_needs_a_defined_variable() {
+        let mut engine = Engine::new();
+
+        assert_eq!(
+            engine.eval("pi += 1").unwrap_err().to_string(),
+            "runtime error: cannot assign to constant `pi`"
+        );
+        assert_eq!(
+            engine.eval("y = 1").unwrap_err().to_string(),
+            "runtime error: cannot assign to undefined variable `y`"
+        );
+        assert!(engine.eval("let y = 1; y -= 1").is_ok());
+        assert_eq!(engine.get_var("y"), Some(Value::from(0)));
+    }
+
+    #[test]
+    fn engine_is_send() {
+        fn assert_send<T: Send>() {}
+
+        assert_send::<Engine>();
+    }
+
+    #[test]
+    fn recursion_fits_the_documented_stack() {
+        let depth = 2000;
+
+        let result = std::thread::Builder::new()
+            .stack_size(Engine::stack_size(depth))
+            .spawn(move || {
+                let mut engine = Engine::new();
+                engine.set_max_depth(depth);
+
+                engine
+                    .eval("fn g(n) = if(n <= 0, 0, 1 + g(n - 1))")
+                    .unwrap();
+
+                (engine.eval("g(1990)"), engine.eval("g(2010)"))
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+
+        assert_eq!(result.0.unwrap(), Value::from(1990));
+        assert!(result.1.is_err());
+    }
+
+    #[test]
+    fn max_depth_is_capped() {
+        let mut engine = Engine::new();
+        engine.set_max_depth(usize::MAX);
+
+        assert_eq!(
+            engine.environment().max_depth(),
+            crate::compiler::environment::MAX_DEPTH_LIMIT
+        );
+    }
+
+    #[test]
+    fn native_functions_see_values() {
+        let mut engine = Engine::new();
+
+        engine
+            .register_function("half", &["x"], |arguments| {
+                Ok(Value::Float(arguments[0].to_f64() / 2.0))
+            })
+            .unwrap();
+        engine
+            .set_var("big", Value::from(BigInt::from(1) << 100))
+            .unwrap();
+
+        assert_eq!(engine.eval("half(3)").unwrap(), Value::Float(1.5));
+        assert_eq!(
+            engine.get_var("big"),
+            Some(Value::from(BigInt::from(1) << 100))
+        );
+        assert_eq!(engine.eval("big / 2 ** 99").unwrap(), Value::Float(2.0));
+        assert!(engine.set_var("unit", Value::Unit).is_err());
+    }
+}
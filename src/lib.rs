@@ -0,0 +1,11 @@
+pub(crate) mod compiler;
+pub mod engine;
+
+pub use compiler::{decimal::Decimal, environment::AngleUnit, error::ScrError};
+pub use engine::{Engine, Value};
+
+// The REPL is built on the compiler itself, which is not part of the library's API.
+#[doc(hidden)]
+pub mod internal {
+    pub use crate::compiler::*;
+}
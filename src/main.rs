@@ -1,11 +1,122 @@
-use std::error::Error;
+use std::{
+    env,
+    error::Error,
+    fs,
+    io::{self, IsTerminal, Read},
+    path::PathBuf,
+    process::ExitCode,
+    thread,
+};
 
-use repl::Repl;
+use repl::{Repl, config, settings::Settings};
+use scr::{Engine, internal::environment::MAX_DEPTH_LIMIT};
 
-pub mod compiler;
-pub mod repl;
+mod repl;
 
-fn main() -> Result<(), Box<dyn Error>> {
-    let mut repl = Repl::new()?;
-    repl.run()
+enum Input {
+    Interactive,
+    Expressions(Vec<String>),
+    File(PathBuf),
+    Stdin,
+}
+
+fn main() -> ExitCode {
+    let mut settings = Settings::default();
+    config::load(&mut settings);
+
+    if let Some(path) = env::var_os("SCR_INIT").filter(|path| !path.is_empty()) {
+        settings.startup = Some(PathBuf::from(path));
+    }
+
+    let input = match parse_arguments(&mut settings) {
+        Ok(input) => input,
+
+        Err(message) => {
+            eprintln!("error: {message}");
+            eprintln!("usage: scr [--caret-power] [--init <path>] [-e <expression>]... [file]");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    // `set depth` can raise the recursion limit at any time, so the REPL needs room for the highest.
+    let result = thread::Builder::new()
+        .stack_size(Engine::stack_size(MAX_DEPTH_LIMIT))
+        .spawn(move || run(settings, input))
+        .map_err(|error| error.into())
+        .and_then(|thread| thread.join().expect("the repl thread panicked"));
+
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn parse_arguments(settings: &mut Settings) -> Result<Input, String> {
+    let mut arguments = env::args().skip(1);
+    let mut expressions = Vec::new();
+    let mut file = None;
+
+    while let Some(argument) = arguments.next() {
+        match argument.as_str() {
+            "--caret-power" => settings.caret_is_power = true,
+
+            "--init" => {
+                let path = arguments.next().ok_or("`--init` expects a path")?;
+                settings.startup = Some(PathBuf::from(path));
+            }
+
+            "-e" => expressions.push(arguments.next().ok_or("`-e` expects an expression")?),
+
+            other if other.starts_with('-') && other != "-" => {
+                return Err(format!("unknown argument `{other}`"));
+            }
+
+            _ if file.is_some() => return Err("only one file can be run".to_string()),
+            path => file = Some(PathBuf::from(path)),
+        }
+    }
+
+    Ok(match file {
+        Some(_) if !expressions.is_empty() => {
+            return Err("`-e` cannot be combined with a file".to_string());
+        }
+
+        None if !expressions.is_empty() => Input::Expressions(expressions),
+        Some(path) if path.as_os_str() == "-" => Input::Stdin,
+        Some(path) => Input::File(path),
+        None if io::stdin().is_terminal() => Input::Interactive,
+        None => Input::Stdin,
+    })
+}
+
+fn run(settings: Settings, input: Input) -> Result<bool, Box<dyn Error + Send + Sync>> {
+    let mut repl = Repl::new(settings)?;
+
+    Ok(match input {
+        Input::Interactive => {
+            repl.run()?;
+            true
+        }
+
+        Input::Expressions(expressions) => repl.run_batch(None, &expressions.join("\n")),
+
+        Input::File(path) => {
+            let content = fs::read_to_string(&path)
+                .map_err(|error| format!("failed to read {}: {error}", path.display()))?;
+
+            repl.run_batch(Some(&path.display().to_string()), &content)
+        }
+
+        Input::Stdin => {
+            let mut content = String::new();
+            io::stdin().read_to_string(&mut content)?;
+
+            repl.run_batch(Some("<stdin>"), &content)
+        }
+    })
 }
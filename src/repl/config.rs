@@ -0,0 +1,131 @@
+use std::{fs, path::PathBuf};
+
+use super::{settings::Settings, xdg_directory};
+
+pub fn path() -> Option<PathBuf> {
+    Some(xdg_directory("XDG_CONFIG_HOME", ".config")?.join("config.toml"))
+}
+
+pub fn init_path() -> Option<PathBuf> {
+    Some(xdg_directory("XDG_CONFIG_HOME", ".config")?.join("init.scr"))
+}
+
+pub fn load(settings: &mut Settings) {
+    let Some(path) = path() else {
+        return;
+    };
+
+    let Ok(content) = fs::read_to_string(&path) else {
+        return;
+    };
+
+    for (number, line) in content.lines().enumerate() {
+        let result = match parse_line(line) {
+            Ok(Some((name, value))) => {
+                settings
+                    .set(&name, &value, 0..0)
+                    .map_err(|error| match error.hint() {
+                        Some(hint) => format!("{}, {hint}", error.message()),
+                        None => error.message().to_string(),
+                    })
+            }
+            Ok(None) => Ok(()),
+            Err(message) => Err(message),
+        };
+
+        if let Err(message) = result {
+            eprintln!("{}:{}: {message}", path.display(), number + 1);
+        }
+    }
+}
+
+pub fn persist(name: &str, value: &str) -> Result<PathBuf, String> {
+    let path = path().ok_or_else(|| "no configuration directory is available".to_string())?;
+    let content = fs::read_to_string(&path).unwrap_or_default();
+    let entry = format!("{name} = {}", quote(value));
+
+    let mut lines = content.lines().map(str::to_string).collect::<Vec<_>>();
+
+    match lines
+        .iter_mut()
+        .find(|line| matches!(parse_line(line), Ok(Some((key, _))) if key == name))
+    {
+        Some(line) => *line = entry,
+        None => lines.push(entry),
+    }
+
+    fs::write(&path, lines.join("\n") + "\n")
+        .map_err(|error| format!("failed to write {}: {error}", path.display()))?;
+
+    Ok(path)
+}
+
+// A small subset of TOML: `key = value` pairs, where the value is a quoted string, an integer or
+// a boolean, with `#` comments.
+fn parse_line(line: &str) -> Result<Option<(String, String)>, String> {
+    let line = line.trim();
+
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(None);
+    }
+
+    let Some((key, value)) = line.split_once('=') else {
+        return Err("expected `key = value`".to_string());
+    };
+
+    let value = value.trim();
+
+    let value = match value.strip_prefix('"') {
+        Some(rest) => {
+            let mut unquoted = String::new();
+            let mut characters = rest.chars();
+
+            loop {
+                match characters.next() {
+                    Some('"') => break,
+                    Some('\\') => match characters.next() {
+                        Some('n') => unquoted.push('\n'),
+                        Some('t') => unquoted.push('\t'),
+                        Some(other) => unquoted.push(other),
+                        None => return Err("unterminated string".to_string()),
+                    },
+                    Some(other) => unquoted.push(other),
+                    None => return Err("unterminated string".to_string()),
+                }
+            }
+
+            match characters.as_str().trim() {
+                rest if rest.is_empty() || rest.starts_with('#') => unquoted,
+                _ => return Err("unexpected text after string".to_string()),
+            }
+        }
+
+        None => match value.split_once('#') {
+            Some((value, _)) => value.trim().to_string(),
+            None => value.to_string(),
+        },
+    };
+
+    Ok(Some((key.trim().to_string(), value)))
+}
+
+pub fn quote(value: &str) -> String {
+    if value.parse::<i128>().is_ok() || value == "true" || value == "false" {
+        return value.to_string();
+    }
+
+    let mut quoted = String::from("\"");
+
+    for character in value.chars() {
+        match character {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            other => quoted.push(other),
+        }
+    }
+
+    quoted.push('"');
+    quoted
+}
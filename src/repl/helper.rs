@@ -0,0 +1,561 @@
+use std::{
+    borrow::Cow,
+    cell::{Cell, RefCell},
+    ops::Range,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use rustyline::{
+    Helper,
+    completion::{Completer, Pair},
+    highlight::{CmdKind, Highlighter},
+    hint::{Hint, Hinter, HistoryHinter},
+    history::{History, SearchDirection},
+    validate::{ValidationContext, ValidationResult, Validator},
+};
+
+use super::settings::{SETTINGS, Settings};
+use scr::{
+    engine::Engine,
+    internal::{
+        builtins::{self, BUILTINS},
+        constants::CONSTANTS,
+        lexer::{Lexer, token::Token},
+        parser::{
+            expression::{Expression, Literal},
+            precedence,
+            syntax::Syntax,
+        },
+    },
+};
+
+pub const COMMANDS: &[&str] = &[
+    "exit", "clear", "help", "list", "history", "set", "config", "let", "fn",
+];
+
+const NUMBER: &str = "33";
+const TEXT: &str = "33";
+const OPERATOR: &str = "36";
+const KEYWORD: &str = "1;35";
+const FUNCTION: &str = "34";
+const VARIABLE: &str = "32";
+const UNKNOWN: &str = "4";
+const ERROR: &str = "31";
+const PARENTHESIS: &str = "1;34";
+const HINT: &str = "2";
+
+const PREVIEW_TIMEOUT: Duration = Duration::from_millis(50);
+const PREVIEW_MAX_BITS: u64 = 4096;
+
+pub enum ReplHint {
+    Preview(String),
+    History(String),
+}
+
+impl Hint for ReplHint {
+    fn display(&self) -> &str {
+        match self {
+            Self::Preview(hint) | Self::History(hint) => hint,
+        }
+    }
+
+    fn completion(&self) -> Option<&str> {
+        match self {
+            Self::Preview(_) => None,
+            Self::History(hint) => Some(hint),
+        }
+    }
+}
+
+pub struct ReplHelper {
+    hinter: HistoryHinter,
+    engine: Rc<RefCell<Engine>>,
+    settings: Rc<RefCell<Settings>>,
+    parenthesis: Cell<Option<usize>>,
+}
+
+impl ReplHelper {
+    pub fn new(engine: Rc<RefCell<Engine>>, settings: Rc<RefCell<Settings>>) -> Self {
+        Self {
+            hinter: HistoryHinter::new(),
+            engine,
+            settings,
+            parenthesis: Cell::new(None),
+        }
+    }
+
+    fn preview(&self, line: &str) -> Option<String> {
+        let engine = self.engine.borrow();
+
+        // Only plain expressions are previewed; anything else could define or change state.
+        let Syntax::Expression(expression) = engine.parse(&join_lines(line)).ok()? else {
+            return None;
+        };
+
+        if let Expression::Literal(
+            Literal::Integer(_)
+            | Literal::BigInteger(_)
+            | Literal::Decimal(_)
+            | Literal::Float(_)
+            | Literal::Complex(_),
+            _,
+        ) = expression
+        {
+            return None;
+        }
+
+        let value = expression
+            .evaluate_until(
+                engine.environment(),
+                Instant::now() + PREVIEW_TIMEOUT,
+                PREVIEW_MAX_BITS,
+            )
+            .ok()?;
+
+        // Printing huge numbers takes long enough to make typing lag.
+        if value.bits() > PREVIEW_MAX_BITS {
+            return None;
+        }
+
+        Some(format!(
+            "  = {}",
+            value.format(&self.settings.borrow().format)
+        ))
+    }
+
+    fn styles(&self, line: &str) -> Vec<(Range<usize>, &'static str)> {
+        let mut styles = Vec::new();
+        let mut offset = 0;
+
+        // A lex error stops the lexer, so the input is lexed in pieces around each bad span.
+        loop {
+            let rest = &line[offset..];
+
+            match Lexer::new(rest.to_string()).tokenize() {
+                Ok(tokens) => {
+                    self.style_tokens(&tokens, offset, &mut styles);
+                    break styles;
+                }
+
+                Err(error) => {
+                    let span = error.span();
+
+                    if let Ok(tokens) = Lexer::new(rest[..span.start].to_string()).tokenize() {
+                        self.style_tokens(&tokens, offset, &mut styles);
+                    }
+
+                    styles.push((offset + span.start..offset + span.end, ERROR));
+                    offset += span.end;
+                }
+            }
+        }
+    }
+
+    fn style_tokens(
+        &self,
+        tokens: &[Token],
+        offset: usize,
+        styles: &mut Vec<(Range<usize>, &'static str)>,
+    ) {
+        for statement in tokens.split(|token| matches!(token, Token::Semicolon(..))) {
+            let mut locals = Vec::new();
+
+            for (position, token) in statement.iter().enumerate() {
+                let style = match token {
+                    Token::Decimal(..) | Token::Float(..) | Token::Imaginary(..) => Some(NUMBER),
+                    Token::Text(..) => Some(TEXT),
+                    Token::Identifier(..) => self.style_name(statement, position, &mut locals),
+
+                    Token::LeftParenthesis(..)
+                    | Token::RightParenthesis(..)
+                    | Token::Comma(..)
+                    | Token::Semicolon(..)
+                    | Token::EndOfLine(..) => None,
+
+                    _ => Some(OPERATOR),
+                };
+
+                if let Some(style) = style {
+                    let span = token.span();
+                    styles.push((offset + span.start..offset + span.end, style));
+                }
+            }
+        }
+    }
+
+    fn style_name(
+        &self,
+        statement: &[Token],
+        position: usize,
+        locals: &mut Vec<String>,
+    ) -> Option<&'static str> {
+        let Token::Identifier(name, ..) = &statement[position] else {
+            unreachable!()
+        };
+
+        let keyword = match &statement[0] {
+            Token::Identifier(keyword, ..) => keyword.as_str(),
+            _ => "",
+        };
+
+        let engine = self.engine.borrow();
+        let environment = engine.environment();
+        let is_call = matches!(
+            statement.get(position + 1),
+            Some(Token::LeftParenthesis(..))
+        );
+        let is_definition = matches!(keyword, "let" | "fn")
+            && !statement[..position]
+                .iter()
+                .any(|token| matches!(token, Token::Assign(..)));
+
+        Some(match name.as_str() {
+            _ if position == 0 && COMMANDS.contains(&name.as_str()) => KEYWORD,
+            _ if matches!(keyword, "set" | "config" | "history") => return None,
+            _ if is_definition && position == 1 && (keyword == "fn" || is_call) => FUNCTION,
+            _ if is_definition && position == 1 => VARIABLE,
+
+            _ if is_definition => {
+                locals.push(name.clone());
+                VARIABLE
+            }
+
+            "if" if is_call => KEYWORD,
+            _ if is_call
+                && (builtins::lookup(name).is_some()
+                    || environment.function(name).is_some()
+                    || environment.native_function(name).is_some()) =>
+            {
+                FUNCTION
+            }
+
+            _ if is_call => UNKNOWN,
+            "ans" | "_" => VARIABLE,
+            _ if name.starts_with('$') => VARIABLE,
+            _ if locals.contains(name) || environment.get(name).is_some() => VARIABLE,
+            _ => UNKNOWN,
+        })
+    }
+}
+
+impl Completer for ReplHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &rustyline::Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let Ok(tokens) = Lexer::new(line[..pos].to_string()).tokenize() else {
+            return Ok((pos, Vec::new()));
+        };
+
+        let [statement @ .., _] = tokens
+            .rsplit(|token| matches!(token, Token::Semicolon(..)))
+            .next()
+            .unwrap()
+        else {
+            unreachable!()
+        };
+
+        let (statement, start, prefix) = match statement {
+            [rest @ .., Token::Identifier(name, index)] if index + name.len() == pos => {
+                (rest, *index, name.as_str())
+            }
+            _ => (statement, pos, ""),
+        };
+
+        let keyword = match statement.first() {
+            Some(Token::Identifier(keyword, ..)) => keyword.as_str(),
+            _ => "",
+        };
+
+        let mut candidates = match (keyword, statement.len()) {
+            (_, 0) => COMMANDS.iter().copied().map(pair).collect(),
+            ("set" | "config", 1) => SETTINGS.iter().copied().map(pair).collect(),
+            ("set" | "config", _) | ("let" | "fn", 1) => return Ok((start, Vec::new())),
+            _ => Vec::new(),
+        };
+
+        let engine = self.engine.borrow();
+        let environment = engine.environment();
+        let is_call = line[pos..].starts_with('(');
+
+        candidates.extend(
+            ["ans"]
+                .into_iter()
+                .chain(CONSTANTS.iter().map(|constant| constant.name))
+                .chain(environment.variables().map(|(name, _)| name.as_str()))
+                .map(pair),
+        );
+
+        candidates.extend(
+            [("if", "if(condition, then, otherwise)".to_string())]
+                .into_iter()
+                .chain(
+                    BUILTINS
+                        .iter()
+                        .map(|builtin| (builtin.name, builtin.signature())),
+                )
+                .chain(
+                    environment
+                        .functions()
+                        .map(|(name, function)| (name.as_str(), function.signature(name))),
+                )
+                .chain(
+                    environment
+                        .native_functions()
+                        .map(|(name, function)| (name.as_str(), function.signature(name))),
+                )
+                .map(|(name, signature)| Pair {
+                    display: signature,
+                    replacement: if is_call {
+                        name.to_string()
+                    } else {
+                        format!("{name}(")
+                    },
+                }),
+        );
+
+        candidates.retain(|candidate| candidate.replacement.starts_with(prefix));
+        candidates.sort_by(|left, right| left.display.cmp(&right.display));
+
+        Ok((start, candidates))
+    }
+}
+
+impl Highlighter for ReplHelper {
+    fn highlight<'l>(&self, line: &'l str, _pos: usize) -> Cow<'l, str> {
+        if !self.settings.borrow().colors {
+            return Cow::Borrowed(line);
+        }
+
+        let mut styles = self.styles(line);
+
+        if let Some(index) = self.parenthesis.get()
+            && let Some(matching) = matching_parenthesis(line, index)
+        {
+            styles.retain(|(span, _)| !span.contains(&index) && !span.contains(&matching));
+            styles.push((index..index + 1, PARENTHESIS));
+            styles.push((matching..matching + 1, PARENTHESIS));
+            styles.sort_by_key(|(span, _)| span.start);
+        }
+
+        if styles.is_empty() {
+            return Cow::Borrowed(line);
+        }
+
+        let mut highlighted = String::with_capacity(line.len() * 2);
+        let mut index = 0;
+
+        for (span, style) in styles {
+            highlighted.push_str(&line[index..span.start]);
+            highlighted.push_str(&format!("\x1b[{style}m{}\x1b[0m", &line[span.clone()]));
+            index = span.end;
+        }
+
+        highlighted.push_str(&line[index..]);
+
+        Cow::Owned(highlighted)
+    }
+
+    fn highlight_hint<'h>(&self, hint: &'h str) -> Cow<'h, str> {
+        if !self.settings.borrow().colors {
+            return Cow::Borrowed(hint);
+        }
+
+        Cow::Owned(format!("\x1b[{HINT}m{hint}\x1b[0m"))
+    }
+
+    fn highlight_char(&self, line: &str, pos: usize, kind: CmdKind) -> bool {
+        if kind == CmdKind::ForcedRefresh || !self.settings.borrow().colors {
+            self.parenthesis.set(None);
+            return false;
+        }
+
+        let previous = self.parenthesis.get();
+        let bytes = line.as_bytes();
+
+        self.parenthesis.set(
+            [Some(pos), pos.checked_sub(1)]
+                .into_iter()
+                .flatten()
+                .find(|&index| matches!(bytes.get(index), Some(b'(' | b')'))),
+        );
+
+        kind == CmdKind::Other || previous.is_some() || self.parenthesis.get().is_some()
+    }
+}
+
+impl Validator for ReplHelper {
+    fn validate(&self, ctx: &mut ValidationContext) -> rustyline::Result<ValidationResult> {
+        Ok(if is_incomplete(ctx.input()) {
+            ValidationResult::Incomplete
+        } else {
+            ValidationResult::Valid(None)
+        })
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = ReplHint;
+
+    fn hint(&self, line: &str, pos: usize, ctx: &rustyline::Context<'_>) -> Option<ReplHint> {
+        if pos < line.len() {
+            return None;
+        }
+
+        if let Some(number) = recall_number(line) {
+            return history_entry(ctx.history(), number).map(|input| {
+                ReplHint::Preview(format!("  → {}", join_lines(&input).replace('\n', " ")))
+            });
+        }
+
+        self.preview(line)
+            .map(ReplHint::Preview)
+            .or_else(|| self.hinter.hint(line, pos, ctx).map(ReplHint::History))
+    }
+}
+
+impl Helper for ReplHelper {}
+
+fn pair(name: &str) -> Pair {
+    Pair {
+        display: name.to_string(),
+        replacement: name.to_string(),
+    }
+}
+
+fn matching_parenthesis(line: &str, index: usize) -> Option<usize> {
+    let bytes = line.as_bytes();
+    let mut depth = 0isize;
+
+    let candidates: Box<dyn Iterator<Item = usize>> = match bytes[index] {
+        b'(' => Box::new(index..bytes.len()),
+        _ => Box::new((0..=index).rev()),
+    };
+
+    for candidate in candidates {
+        depth += match bytes[candidate] {
+            b'(' => 1,
+            b')' => -1,
+            _ => 0,
+        };
+
+        if depth == 0 {
+            return Some(candidate);
+        }
+    }
+
+    None
+}
+
+// Removes line continuations, a backslash with optional blanks after it, by turning them and the
+// line break into spaces; keeping the length keeps byte offsets, and so error spans, intact.
+pub fn join_lines(input: &str) -> String {
+    let mut lines = input.split('\n').peekable();
+    let mut joined = String::with_capacity(input.len());
+
+    while let Some(line) = lines.next() {
+        let trimmed = line.trim_end_matches([' ', '\t']);
+
+        match trimmed.strip_suffix('\\') {
+            Some(rest) if lines.peek().is_some() => {
+                joined.push_str(rest);
+                joined.push_str(&" ".repeat(line.len() - rest.len() + 1));
+            }
+
+            _ if lines.peek().is_some() => {
+                joined.push_str(line);
+                joined.push('\n');
+            }
+
+            _ => joined.push_str(line),
+        }
+    }
+
+    joined
+}
+
+// As an expression, `!12` would always be 0, so a line of just that recalls input 12 instead.
+pub fn recall_number(line: &str) -> Option<&str> {
+    line.trim()
+        .strip_prefix('!')
+        .filter(|number| !number.is_empty() && number.bytes().all(|b| b.is_ascii_digit()))
+}
+
+pub fn history_entry(history: &dyn History, number: &str) -> Option<String> {
+    let index = number.parse::<usize>().ok()?.checked_sub(1)?;
+
+    history
+        .get(index, SearchDirection::Forward)
+        .ok()?
+        .map(|result| result.entry.into_owned())
+}
+
+pub fn is_incomplete(input: &str) -> bool {
+    if input.trim_end_matches([' ', '\t']).ends_with('\\') {
+        return true;
+    }
+
+    let Ok(tokens) = Lexer::new(join_lines(input)).tokenize() else {
+        return false;
+    };
+
+    let depth = tokens.iter().fold(0isize, |depth, token| match token {
+        Token::LeftParenthesis(..) => depth + 1,
+        Token::RightParenthesis(..) => depth - 1,
+        _ => depth,
+    });
+
+    // A trailing `%` reads like a percentage sign, so it is reported rather than waited on.
+    let trailing_operator = tokens.iter().rev().nth(1).is_some_and(|token| {
+        (precedence::infix(token).is_some() && !matches!(token, Token::Modulo(..)))
+            || token.compound_operator().is_some()
+            || matches!(token, Token::Assign(..) | Token::Comma(..))
+    });
+
+    depth > 0 || trailing_operator
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn continuations_are_joined_like_they_are_detected() {
+        for input in ["1 + \\", "1 + \\ ", "1 + \\\t "] {
+            assert!(is_incomplete(input));
+
+            let joined = join_lines(&format!("{input}\n2"));
+            assert_eq!(joined.len(), input.len() + 2);
+            assert_eq!(
+                joined.split_whitespace().collect::<Vec<_>>(),
+                ["1", "+", "2"]
+            );
+        }
+
+        assert_eq!(join_lines("(1\n+ 2)"), "(1\n+ 2)");
+        assert_eq!(join_lines("1 \\"), "1 \\");
+    }
+
+    #[test]
+    fn a_trailing_percent_sign_is_not_a_continuation() {
+        assert!(!is_incomplete("5 %"));
+        assert!(!is_incomplete("%"));
+        assert!(is_incomplete("5 *"));
+        assert!(is_incomplete("x %="));
+    }
+
+    #[test]
+    fn only_a_whole_line_of_digits_is_recalled() {
+        assert_eq!(recall_number("!12"), Some("12"));
+        assert_eq!(recall_number(" !3 "), Some("3"));
+
+        for line in ["!", "!x", "!12 + 1", "!(12)", "1 + !12", "! 12"] {
+            assert_eq!(recall_number(line), None);
+        }
+    }
+}
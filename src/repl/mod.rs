@@ -0,0 +1,508 @@
+use std::{
+    cell::RefCell,
+    env,
+    error::Error,
+    fs, mem,
+    ops::Range,
+    path::{Path, PathBuf},
+    process::Command,
+    rc::Rc,
+};
+
+use helper::ReplHelper;
+use rustyline::{
+    ColorMode, CompletionType, Config, Editor,
+    config::Configurer,
+    error::ReadlineError,
+    history::{FileHistory, History},
+};
+use settings::{SETTINGS, Settings};
+
+use scr::{
+    engine::{Engine, Value},
+    internal::{
+        constants::CONSTANTS, decimal::DEFAULT_PRECISION, error::ScrError, parser::syntax::Syntax,
+    },
+};
+
+pub mod config;
+mod helper;
+pub mod settings;
+
+enum Outcome {
+    Continue,
+    Exit,
+}
+
+pub struct Repl {
+    editor: Editor<ReplHelper, FileHistory>,
+    engine: Rc<RefCell<Engine>>,
+    // How many entries the history has ever held, which keeps an entry identifiable after older
+    // ones are dropped, and the entry each numbered result came from.
+    entries: usize,
+    result_entries: Vec<Option<usize>>,
+    entry: Option<usize>,
+    settings: Rc<RefCell<Settings>>,
+    history_path: Option<PathBuf>,
+    is_batch: bool,
+}
+
+impl Repl {
+    pub fn new(settings: Settings) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let color_mode = match env::var_os("NO_COLOR") {
+            Some(value) if !value.is_empty() => ColorMode::Disabled,
+            _ => ColorMode::Enabled,
+        };
+
+        let config = Config::builder()
+            .history_ignore_space(true)
+            .color_mode(color_mode)
+            .completion_type(CompletionType::List)
+            .build();
+
+        let engine = Rc::new(RefCell::new(Engine::new()));
+        let settings = Rc::new(RefCell::new(settings));
+        let helper = ReplHelper::new(engine.clone(), settings.clone());
+        let mut editor = Editor::with_config(config)?;
+
+        editor.set_helper(Some(helper));
+
+        let mut repl = Self {
+            editor,
+            engine,
+            entries: 0,
+            result_entries: Vec::new(),
+            entry: None,
+            settings,
+            history_path: xdg_directory("XDG_STATE_HOME", ".local/state")
+                .map(|directory| directory.join("history")),
+            is_batch: false,
+        };
+
+        // The history size has to be in place before loading, or the file would be truncated to
+        // rustyline's default.
+        repl.apply_settings()?;
+
+        if let Some(path) = &repl.history_path
+            && path.exists()
+            && let Err(error) = repl.editor.load_history(path)
+        {
+            eprintln!("failed to load history from {}: {error}", path.display());
+        }
+
+        repl.entries = repl.editor.history().len();
+
+        Ok(repl)
+    }
+
+    pub fn run(&mut self) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if self.settings.borrow().banner {
+            println!(
+                "Welcome to scr (simple calculation repl), double press tab to show available commands in the completion list.\n"
+            );
+        }
+
+        self.run_startup();
+
+        loop {
+            let prompt = self.settings.borrow().prompt(self.result_entries.len() + 1);
+            let line = match self.editor.readline(&prompt) {
+                Ok(line) => line,
+                Err(ReadlineError::Eof) => break Ok(()),
+                Err(error) => return Err(error.into()),
+            };
+
+            let line = match self.recall(&line) {
+                Ok(line) => line,
+
+                Err(error) => {
+                    eprintln!("{}", error.render(&line));
+                    continue;
+                }
+            };
+
+            if self.editor.add_history_entry(&line)? {
+                self.entries += 1;
+            }
+
+            // The history skips some lines, like those starting with a space.
+            self.entry =
+                (self.editor.history().iter().next_back() == Some(&line)).then(|| self.entries - 1);
+            self.save_history();
+
+            let line = helper::join_lines(&line);
+
+            match self.execute_line(&line) {
+                Ok(Outcome::Exit) => break Ok(()),
+                Ok(Outcome::Continue) => {}
+                Err(error) => eprintln!("{}", error.render(&line)),
+            }
+        }
+    }
+
+    pub fn run_batch(&mut self, name: Option<&str>, content: &str) -> bool {
+        self.is_batch = true;
+        self.run_startup();
+        self.run_script(name, content, false).is_ok()
+    }
+
+    fn run_startup(&mut self) {
+        let startup = match self.settings.borrow().startup.clone() {
+            Some(path) => Some(expand_home(&path)),
+            None => config::init_path().filter(|path| path.exists()),
+        };
+
+        let Some(path) = startup else {
+            return;
+        };
+
+        match fs::read_to_string(&path) {
+            Ok(content) => {
+                let _ = self.run_script(Some(&path.display().to_string()), &content, true);
+            }
+
+            Err(error) => eprintln!("failed to read {}: {error}", path.display()),
+        }
+    }
+
+    fn run_script(
+        &mut self,
+        name: Option<&str>,
+        content: &str,
+        keep_going: bool,
+    ) -> Result<Outcome, ()> {
+        let mut statement = String::new();
+        let mut first_line = 1;
+
+        for (number, line) in content.lines().enumerate() {
+            if statement.is_empty() {
+                first_line = number + 1;
+            } else {
+                statement.push('\n');
+            }
+
+            statement.push_str(line);
+
+            if helper::is_incomplete(&statement) {
+                continue;
+            }
+
+            match self.run_statement(name, first_line, &statement) {
+                Ok(Outcome::Exit) => return Ok(Outcome::Exit),
+                Err(()) if !keep_going => return Err(()),
+                _ => statement.clear(),
+            }
+        }
+
+        if statement.is_empty() {
+            Ok(Outcome::Continue)
+        } else {
+            self.run_statement(name, first_line, &statement)
+        }
+    }
+
+    fn run_statement(
+        &mut self,
+        name: Option<&str>,
+        first_line: usize,
+        statement: &str,
+    ) -> Result<Outcome, ()> {
+        let statement = helper::join_lines(statement);
+
+        self.execute_line(&statement).map_err(|error| {
+            let rendered = error.render(&statement);
+
+            match name {
+                Some(name) => {
+                    let start = error.span().start.min(statement.len());
+                    let line = first_line + statement[..start].matches('\n').count();
+
+                    eprintln!("{name}:{line}: {rendered}");
+                }
+
+                None => eprintln!("{rendered}"),
+            }
+        })
+    }
+
+    fn execute_line(&mut self, line: &str) -> Result<Outcome, ScrError> {
+        let statements = match self.engine.borrow().parse(line)? {
+            Syntax::Block(statements) => statements,
+            syntax => vec![syntax],
+        };
+
+        // The engine takes everything between REPL commands as one input, so only its last
+        // expression gives a numbered result.
+        let mut input = Vec::new();
+
+        for statement in statements {
+            match statement {
+                Syntax::Command(..)
+                | Syntax::History(..)
+                | Syntax::Set(..)
+                | Syntax::Config(..) => {
+                    self.evaluate(mem::take(&mut input))?;
+
+                    if let Outcome::Exit = self.execute(statement, line)? {
+                        return Ok(Outcome::Exit);
+                    }
+                }
+
+                statement => input.push(statement),
+            }
+        }
+
+        self.evaluate(input)?;
+
+        Ok(Outcome::Continue)
+    }
+
+    fn evaluate(&mut self, input: Vec<Syntax>) -> Result<(), ScrError> {
+        if input.is_empty() {
+            return Ok(());
+        }
+
+        if let Value::Unit = self.engine.borrow_mut().execute(Syntax::Block(input))? {
+            return Ok(());
+        }
+
+        self.result_entries.push(self.entry);
+
+        let engine = self.engine.borrow();
+        let results = engine.environment().results();
+        let value = results[results.len() - 1].format(&self.settings.borrow().format);
+
+        if self.is_batch {
+            println!("{value}");
+        } else {
+            println!("${} = {value}", results.len());
+        }
+
+        Ok(())
+    }
+
+    fn apply_settings(&mut self) -> rustyline::Result<()> {
+        let settings = self.settings.borrow();
+        let mut engine = self.engine.borrow_mut();
+
+        engine.set_caret_power(settings.caret_is_power);
+        engine.set_max_depth(settings.depth);
+        engine.set_angle_unit(settings.format.angle_unit);
+        engine.set_exact(settings.is_exact);
+        engine.set_decimal(
+            settings.is_decimal,
+            settings.format.precision.unwrap_or(DEFAULT_PRECISION),
+        );
+
+        self.editor.set_edit_mode(settings.edit_mode);
+        self.editor.set_max_history_size(settings.history_size)?;
+        self.editor.set_history_ignore_dups(settings.history_dedupe)
+    }
+
+    fn set(&mut self, name: &str, value: &str, span: Range<usize>) -> Result<(), ScrError> {
+        self.settings.borrow_mut().set(name, value, span.clone())?;
+
+        self.apply_settings().map_err(|error| {
+            ScrError::Runtime(format!("failed to apply `{name}`: {error}"), span, None)
+        })
+    }
+
+    fn recall(&self, line: &str) -> Result<String, ScrError> {
+        let Some(number) = helper::recall_number(line) else {
+            return Ok(line.to_string());
+        };
+
+        let input = helper::history_entry(self.editor.history(), number).ok_or_else(|| {
+            let start = line.find('!').unwrap();
+
+            ScrError::Runtime(
+                format!("no input numbered {number}"),
+                start..start + number.len() + 1,
+                Some("`history` lists the numbered inputs".to_string()),
+            )
+        })?;
+
+        println!("{input}");
+
+        Ok(input)
+    }
+
+    fn save_history(&mut self) {
+        if let Some(path) = &self.history_path
+            && let Err(error) = self.editor.append_history(path)
+        {
+            eprintln!("failed to save history to {}: {error}", path.display());
+            self.history_path = None;
+        }
+    }
+
+    fn execute(&mut self, syntax: Syntax, line: &str) -> Result<Outcome, ScrError> {
+        match syntax {
+            Syntax::Command(name) => match name.as_str() {
+                "exit" => return Ok(Outcome::Exit),
+
+                "clear" => {
+                    Command::new("clear").status().map_err(|error| {
+                        ScrError::Runtime(
+                            format!("failed to clear the screen: {error}"),
+                            0..line.len(),
+                            None,
+                        )
+                    })?;
+                }
+                "list" => {
+                    let format = self.settings.borrow().format;
+
+                    let engine = self.engine.borrow();
+
+                    for (name, value) in engine.environment().variables() {
+                        println!("{name} = {}", value.format(&format));
+                    }
+
+                    for (name, function) in engine.environment().functions() {
+                        println!("{} = {}", function.signature(name), function.body);
+                    }
+
+                    for (name, function) in engine.environment().native_functions() {
+                        println!("{} = <native>", function.signature(name));
+                    }
+
+                    println!("\nconstants:");
+
+                    // In decimal mode the constants are computed to the configured precision.
+                    for constant in CONSTANTS {
+                        println!(
+                            "    {} = {} ({})",
+                            constant.name,
+                            engine
+                                .environment()
+                                .constant(constant.name)
+                                .unwrap()
+                                .format(&format),
+                            constant.description
+                        );
+                    }
+                }
+
+                "config" => {
+                    match config::path() {
+                        Some(path) => println!("{}:", path.display()),
+                        None => println!("no configuration directory is available:"),
+                    }
+
+                    let settings = self.settings.borrow();
+
+                    for name in SETTINGS {
+                        println!("    {name} = {}", config::quote(&settings.get(name).unwrap()));
+                    }
+                }
+
+                "help" => println!(
+                    "
+scr (simple calculation REPL) is a simplistic math REPL for quick calculations:
+    Every result is numbered and can be reused as `$1`, `$2`, ..., the last one also as `ans` or `_`.
+    Available commands:
+        exit    - Exits the REPL.
+        clear   - Clears the current terminal screen.
+        let     - Defines variables, e.g. `let x = 2`, or functions, e.g. `let f(x, y) = x ** 2 + y`.
+                  Defined variables can be reassigned with `x = 3` or `x += 1` (also -=, *=, /=,
+                  %=, **=, <<=, >>=, &=, |= and ^=).
+        fn      - Defines functions, e.g. `fn fact(n) = if(n <= 1, 1, n * fact(n - 1))`.
+        history - Lists the inputs of this and earlier sessions with the numbered results of this
+                  one, `history sin` only those containing `sin`. `!12` evaluates input 12 again.
+                  Inputs are kept in $XDG_STATE_HOME/scr/history for the up arrow and Ctrl-R.
+        list    - Lists all variables and functions in order with their values respectfully, followed by
+                  the read-only constants (pi, e, i, c, h, G, k_B, N_A, ...).
+        set     - Changes a setting for this session, e.g. `set caret power` makes `^` exponentiation
+                  (`xor` stays available for bitwise exclusive or), `set caret xor` restores it.
+        config  - Lists the settings, `config <name> <value>` changes one and saves it to the
+                  config file ($XDG_CONFIG_HOME/scr/config.toml, `name = value` per line):
+                      angle     - `radians` or `degrees` for trigonometric functions
+                      banner    - whether to print the welcome message
+                      base      - 2, 8, 10 or 16 for integer results
+                      caret     - `power` or `xor`
+                      colors    - whether to highlight input
+                      complex   - `rectangular` (`3+4i`) or `polar` (`5∠0.927`) display of
+                                  complex results like `sqrt(-1)`
+                      decimal   - whether to compute in decimal floating point, so
+                                  `0.1 + 0.2` is exactly `0.3`
+                      dedupe    - whether repeating the previous input adds it to the
+                                  history only once
+                      depth     - how deeply functions may recurse
+                      edit      - `vi` or `emacs` key bindings
+                      exact     - whether dividing integers gives exact fractions like
+                                  `1/3` instead of floats, `float(x)` converts them
+                      fractions - `improper` (`4/3`) or `mixed` (`1 1/3`) display
+                      history   - how many inputs are remembered
+                      precision - significant digits of float results (up to 17) and of
+                                  decimal arithmetic (up to 100), or `auto` (34 for decimals)
+                      prompt    - the prompt, `{{n}}` is replaced by the input number
+                      startup   - a file of definitions to run at startup instead of
+                                  $XDG_CONFIG_HOME/scr/init.scr, also set by `--init <path>`
+                                  or $SCR_INIT
+        help    - Prints this message.
+"
+                ),
+
+                _ => {}
+            },
+
+            Syntax::History(search) => {
+                let history = self.editor.history();
+                let first = self.entries - history.len();
+                let engine = self.engine.borrow();
+                let format = self.settings.borrow().format;
+
+                for (index, input) in history.iter().enumerate().filter(|(_, input)| {
+                    search.as_ref().is_none_or(|search| input.contains(search))
+                }) {
+                    println!("[{}] {input}", index + 1);
+
+                    for (number, (_, value)) in self
+                        .result_entries
+                        .iter()
+                        .zip(engine.environment().results())
+                        .enumerate()
+                        .filter(|(_, (entry, _))| **entry == Some(first + index))
+                    {
+                        println!("    ${} = {}", number + 1, value.format(&format));
+                    }
+                }
+            }
+
+            Syntax::Set(name, value, index) => self.set(&name, &value, index..index + name.len())?,
+
+            Syntax::Config(name, value, index) => {
+                let span = index..index + name.len();
+                self.set(&name, &value, span.clone())?;
+
+                let value = self.settings.borrow().get(&name).unwrap();
+                config::persist(&name, &value)
+                    .map_err(|message| ScrError::Runtime(message, span, None))?;
+            }
+
+            _ => unreachable!(),
+        }
+
+        Ok(Outcome::Continue)
+    }
+}
+
+fn xdg_directory(variable: &str, fallback: &str) -> Option<PathBuf> {
+    let directory = env::var_os(variable)
+        .filter(|directory| !directory.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(fallback)))?
+        .join("scr");
+
+    fs::create_dir_all(&directory).ok()?;
+
+    Some(directory)
+}
+
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), env::var_os("HOME")) {
+        (Ok(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => path.to_path_buf(),
+    }
+}
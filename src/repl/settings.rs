@@ -0,0 +1,257 @@
+use std::{ops::Range, path::PathBuf};
+
+use rustyline::EditMode;
+
+use scr::internal::{
+    environment::{AngleUnit, DEFAULT_MAX_DEPTH, MAX_DEPTH_LIMIT},
+    error::{ScrError, suggest},
+    format::Format,
+};
+
+pub const SETTINGS: &[&str] = &[
+    "angle",
+    "banner",
+    "base",
+    "caret",
+    "colors",
+    "complex",
+    "decimal",
+    "dedupe",
+    "depth",
+    "edit",
+    "exact",
+    "fractions",
+    "history",
+    "precision",
+    "prompt",
+    "startup",
+];
+
+pub const DEFAULT_HISTORY_SIZE: usize = 1000;
+pub const DEFAULT_PROMPT: &str = "[{n}] scr > ";
+
+#[derive(Clone)]
+pub struct Settings {
+    pub banner: bool,
+    pub caret_is_power: bool,
+    pub colors: bool,
+    pub is_decimal: bool,
+    pub depth: usize,
+    pub edit_mode: EditMode,
+    pub is_exact: bool,
+    pub format: Format,
+    pub history_size: usize,
+    pub history_dedupe: bool,
+    pub prompt: String,
+    pub startup: Option<PathBuf>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            banner: true,
+            caret_is_power: false,
+            colors: true,
+            is_decimal: false,
+            depth: DEFAULT_MAX_DEPTH,
+            edit_mode: EditMode::Vi,
+            is_exact: false,
+            format: Format::default(),
+            history_size: DEFAULT_HISTORY_SIZE,
+            history_dedupe: true,
+            prompt: DEFAULT_PROMPT.to_string(),
+            startup: None,
+        }
+    }
+}
+
+impl Settings {
+    pub fn prompt(&self, number: usize) -> String {
+        self.prompt.replace("{n}", &number.to_string())
+    }
+
+    pub fn get(&self, name: &str) -> Option<String> {
+        Some(match name {
+            "angle" => match self.format.angle_unit {
+                AngleUnit::Radians => "radians".to_string(),
+                AngleUnit::Degrees => "degrees".to_string(),
+            },
+            "banner" => self.banner.to_string(),
+            "base" => self.format.base.to_string(),
+            "caret" if self.caret_is_power => "power".to_string(),
+            "caret" => "xor".to_string(),
+            "colors" => self.colors.to_string(),
+            "complex" if self.format.is_polar => "polar".to_string(),
+            "complex" => "rectangular".to_string(),
+            "decimal" => self.is_decimal.to_string(),
+            "dedupe" => self.history_dedupe.to_string(),
+            "depth" => self.depth.to_string(),
+            "edit" if self.edit_mode == EditMode::Vi => "vi".to_string(),
+            "edit" => "emacs".to_string(),
+            "exact" => self.is_exact.to_string(),
+            "fractions" if self.format.is_mixed => "mixed".to_string(),
+            "fractions" => "improper".to_string(),
+            "history" => self.history_size.to_string(),
+            "precision" => match self.format.precision {
+                Some(precision) => precision.to_string(),
+                None => "auto".to_string(),
+            },
+            "prompt" => self.prompt.clone(),
+            "startup" => match &self.startup {
+                Some(path) => path.display().to_string(),
+                None => String::new(),
+            },
+            _ => return None,
+        })
+    }
+
+    pub fn set(&mut self, name: &str, value: &str, span: Range<usize>) -> Result<(), ScrError> {
+        let invalid = |expected: String| {
+            ScrError::Runtime(
+                format!("invalid value for {name}: {value}"),
+                span.clone(),
+                Some(expected),
+            )
+        };
+
+        match name {
+            "angle" => {
+                self.format.angle_unit = match value {
+                    "radians" | "rad" => AngleUnit::Radians,
+                    "degrees" | "deg" => AngleUnit::Degrees,
+                    _ => return Err(invalid("expected `radians` or `degrees`".to_string())),
+                }
+            }
+
+            "banner" => {
+                self.banner =
+                    parse_switch(value).ok_or_else(|| invalid(SWITCH_VALUES.to_string()))?
+            }
+
+            "base" => {
+                self.format.base = match value.parse() {
+                    Ok(base @ (2 | 8 | 10 | 16)) => base,
+                    _ => return Err(invalid("expected 2, 8, 10 or 16".to_string())),
+                }
+            }
+
+            "caret" => {
+                self.caret_is_power = match value {
+                    "power" => true,
+                    "xor" => false,
+                    _ => return Err(invalid("expected `power` or `xor`".to_string())),
+                }
+            }
+
+            "colors" => {
+                self.colors =
+                    parse_switch(value).ok_or_else(|| invalid(SWITCH_VALUES.to_string()))?
+            }
+
+            "complex" => {
+                self.format.is_polar = match value {
+                    "polar" => true,
+                    "rectangular" | "rect" => false,
+                    _ => return Err(invalid("expected `rectangular` or `polar`".to_string())),
+                }
+            }
+
+            "decimal" => {
+                self.is_decimal =
+                    parse_switch(value).ok_or_else(|| invalid(SWITCH_VALUES.to_string()))?
+            }
+
+            "dedupe" => {
+                self.history_dedupe =
+                    parse_switch(value).ok_or_else(|| invalid(SWITCH_VALUES.to_string()))?
+            }
+
+            "depth" => {
+                self.depth = match value.parse() {
+                    Ok(depth) if depth <= MAX_DEPTH_LIMIT => depth,
+                    _ => {
+                        return Err(invalid(format!(
+                            "expected an integer between 0 and {MAX_DEPTH_LIMIT}"
+                        )));
+                    }
+                }
+            }
+
+            "edit" => {
+                self.edit_mode = match value {
+                    "vi" => EditMode::Vi,
+                    "emacs" => EditMode::Emacs,
+                    _ => return Err(invalid("expected `vi` or `emacs`".to_string())),
+                }
+            }
+
+            "exact" => {
+                self.is_exact =
+                    parse_switch(value).ok_or_else(|| invalid(SWITCH_VALUES.to_string()))?
+            }
+
+            "fractions" => {
+                self.format.is_mixed = match value {
+                    "mixed" => true,
+                    "improper" => false,
+                    _ => return Err(invalid("expected `improper` or `mixed`".to_string())),
+                }
+            }
+
+            "history" => {
+                self.history_size = value
+                    .parse()
+                    .map_err(|_| invalid("expected a non-negative integer".to_string()))?
+            }
+
+            "precision" => {
+                self.format.precision = match value {
+                    "auto" => None,
+                    _ => match value.parse() {
+                        Ok(precision @ 1..=MAX_PRECISION) => Some(precision),
+                        _ => {
+                            return Err(invalid(format!(
+                                "expected `auto` or a number of significant digits between 1 and {MAX_PRECISION}"
+                            )));
+                        }
+                    },
+                }
+            }
+
+            "prompt" => self.prompt = value.to_string(),
+            "startup" if value.is_empty() => self.startup = None,
+            "startup" => self.startup = Some(PathBuf::from(value)),
+
+            other => {
+                return Err(ScrError::Runtime(
+                    format!("unknown setting `{other}`"),
+                    span,
+                    suggest(other, SETTINGS.iter().copied()),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+const MAX_PRECISION: usize = 100;
+const SWITCH_VALUES: &str = "expected `true` or `false`";
+
+fn parse_switch(value: &str) -> Option<bool> {
+    match value {
+        "true" | "on" => Some(true),
+        "false" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_prompt_shows_the_next_result_number() {
+        assert_eq!(Settings::default().prompt(3), "[3] scr > ");
+    }
+}